@@ -0,0 +1,286 @@
+use std::collections::BTreeMap;
+
+use agentifa_555nake_protocol::protocol::Direction;
+use bevy::{input::Input, prelude::KeyCode};
+
+use crate::storage;
+
+const BINDINGS_KEY: &str = "bindings";
+const BINDINGS_MAX: usize = 2; // Keys per action
+
+/// Keys that can be bound
+pub const KEYS: [(KeyCode, &str); 66] = [
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::F1, "F1"),
+    (KeyCode::F2, "F2"),
+    (KeyCode::F3, "F3"),
+    (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"),
+    (KeyCode::F6, "F6"),
+    (KeyCode::F7, "F7"),
+    (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"),
+    (KeyCode::F10, "F10"),
+    (KeyCode::F11, "F11"),
+    (KeyCode::F12, "F12"),
+    (KeyCode::Back, "Backspace"),
+    (KeyCode::Comma, "Comma"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Escape, "Escape"),
+    (KeyCode::LAlt, "LAlt"),
+    (KeyCode::LControl, "LControl"),
+    (KeyCode::LShift, "LShift"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Minus, "Minus"),
+    (KeyCode::Period, "Period"),
+    (KeyCode::RAlt, "RAlt"),
+    (KeyCode::RControl, "RControl"),
+    (KeyCode::RShift, "RShift"),
+    (KeyCode::Return, "Return"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Up, "Up"),
+];
+
+/// Things the player does with the keyboard, every screen reads its keys
+/// through the `Bindings`.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    Back,
+    Boost,
+    Chat,
+    ChatHide,
+    Confirm,
+    Delete,
+    Lock,
+    Pause,
+    Ready,
+    Steer(Direction),
+    Switch,
+}
+
+impl Action {
+    pub const ALL: [Action; 14] = [
+        Action::Steer(Direction::Up),
+        Action::Steer(Direction::Down),
+        Action::Steer(Direction::Left),
+        Action::Steer(Direction::Right),
+        Action::Boost,
+        Action::Confirm,
+        Action::Back,
+        Action::Delete,
+        Action::Switch,
+        Action::Chat,
+        Action::ChatHide,
+        Action::Ready,
+        Action::Pause,
+        Action::Lock,
+    ];
+
+    fn defaults(&self) -> Vec<KeyCode> {
+        match self {
+            Action::Back => vec![KeyCode::Escape],
+            Action::Boost => vec![KeyCode::Space],
+            Action::Chat => vec![KeyCode::T],
+            Action::ChatHide => vec![KeyCode::F2],
+            Action::Confirm => vec![KeyCode::Return],
+            Action::Delete => vec![KeyCode::Back],
+            Action::Lock => vec![KeyCode::L],
+            Action::Pause => vec![KeyCode::P],
+            Action::Ready => vec![KeyCode::R],
+            Action::Steer(Direction::Down) => vec![KeyCode::Down, KeyCode::S],
+            Action::Steer(Direction::Left) => vec![KeyCode::Left, KeyCode::A],
+            Action::Steer(Direction::Right) => vec![KeyCode::Right, KeyCode::D],
+            Action::Steer(Direction::Up) => vec![KeyCode::Up, KeyCode::W],
+            Action::Switch => vec![KeyCode::Tab],
+        }
+    }
+
+    /// Returns false for keys that type text if the action is used while
+    /// typing, so that the register and chat screens keep their letters.
+    pub fn accepts(&self, key: KeyCode) -> bool {
+        match self {
+            Action::Back | Action::Confirm | Action::Delete | Action::Switch => !is_text(key),
+            _ => true,
+        }
+    }
+
+    /// Name of the action in the settings and the saved bindings
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Back => "Back",
+            Action::Boost => "Boost",
+            Action::Chat => "Chat",
+            Action::ChatHide => "Hide chat",
+            Action::Confirm => "Confirm",
+            Action::Delete => "Delete",
+            Action::Lock => "Lock room",
+            Action::Pause => "Pause room",
+            Action::Ready => "Ready",
+            Action::Steer(Direction::Down) => "Down",
+            Action::Steer(Direction::Left) => "Left",
+            Action::Steer(Direction::Right) => "Right",
+            Action::Steer(Direction::Up) => "Up",
+            Action::Switch => "Switch",
+        }
+    }
+}
+
+/// Keys bound to each action. They are saved as a JSON object of action and
+/// key names in the storage.
+pub struct Bindings {
+    keys: Vec<(Action, Vec<KeyCode>)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            keys: Action::ALL.iter().map(|a| (*a, a.defaults())).collect(),
+        }
+    }
+}
+
+impl Bindings {
+    /// Binds `key` to `action` in front of its other keys and takes it from
+    /// every other action. Returns false if the action does not accept it.
+    pub fn bind(&mut self, action: Action, key: KeyCode) -> bool {
+        if !action.accepts(key) {
+            return false;
+        }
+
+        for (a, keys) in self.keys.iter_mut() {
+            keys.retain(|k| *k != key);
+            if *a == action {
+                keys.insert(0, key);
+                keys.truncate(BINDINGS_MAX);
+            }
+        }
+
+        true
+    }
+
+    pub fn just_pressed(&self, input: &Input<KeyCode>, action: Action) -> bool {
+        self.keys(action).iter().any(|k| input.just_pressed(*k))
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.keys
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Reads the saved bindings, actions missing there keep their defaults.
+    pub fn load() -> Self {
+        let mut bindings = Bindings::default();
+        let saved: BTreeMap<String, Vec<String>> =
+            match storage::load(BINDINGS_KEY).and_then(|j| serde_json::from_str(&j).ok()) {
+                Some(saved) => saved,
+                None => return bindings,
+            };
+
+        for (action, keys) in bindings.keys.iter_mut() {
+            if let Some(names) = saved.get(action.label()) {
+                *keys = names
+                    .iter()
+                    .filter_map(|n| key_code(n))
+                    .filter(|k| action.accepts(*k))
+                    .collect();
+            }
+        }
+
+        bindings
+    }
+
+    pub fn pressed(&self, input: &Input<KeyCode>, action: Action) -> bool {
+        self.keys(action).iter().any(|k| input.pressed(*k))
+    }
+
+    pub fn reset(&self, input: &mut Input<KeyCode>, action: Action) {
+        for key in self.keys(action) {
+            input.reset(*key);
+        }
+    }
+
+    pub fn save(&self) {
+        let saved: BTreeMap<&str, Vec<&str>> = self
+            .keys
+            .iter()
+            .map(|(a, keys)| (a.label(), keys.iter().filter_map(key_name).collect()))
+            .collect();
+
+        if let Ok(json) = serde_json::to_string(&saved) {
+            storage::save(BINDINGS_KEY, &json);
+        }
+    }
+
+    /// Checks and releases the keys of `action`, so that the next screen does
+    /// not see them.
+    pub fn take(&self, input: &mut Input<KeyCode>, action: Action) -> bool {
+        let keys: Vec<KeyCode> = self
+            .keys(action)
+            .iter()
+            .copied()
+            .filter(|k| input.pressed(*k))
+            .collect();
+
+        for key in keys.iter() {
+            input.release(*key);
+        }
+
+        !keys.is_empty()
+    }
+}
+
+fn key_code(name: &str) -> Option<KeyCode> {
+    KEYS.iter().find(|(_, n)| *n == name).map(|(k, _)| *k)
+}
+
+/// Letters, digits and the keys of the punctuation that can be typed
+fn is_text(key: KeyCode) -> bool {
+    match key {
+        KeyCode::Comma | KeyCode::Minus | KeyCode::Period | KeyCode::Space => true,
+        _ => key_name(&key).map_or(false, |n| n.len() == 1),
+    }
+}
+
+pub fn key_name(key: &KeyCode) -> Option<&'static str> {
+    KEYS.iter().find(|(k, _)| k == key).map(|(_, n)| *n)
+}
@@ -0,0 +1,362 @@
+use std::collections::VecDeque;
+
+use agentifa_555nake_protocol::protocol::{ChatChannel, ChatCmd, Protocol, CHAT_SIZE};
+use bevy::{
+    input::Input,
+    math::Vec3,
+    prelude::{
+        Added, App, BuildChildren, ButtonBundle, Changed, Color, Commands, Component,
+        DespawnRecursiveExt, Entity, EventReader, EventWriter, Interaction, KeyCode, NodeBundle,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut, SystemSet, TextBundle,
+        Transform, Visibility, With,
+    },
+    text::{Text, TextSection, TextStyle},
+    ui::{AlignItems, FlexDirection, JustifyContent, Size, Style, UiRect, Val},
+    window::ReceivedCharacter,
+};
+use naia_bevy_client::{events::MessageEvent, shared::DefaultChannels, Client};
+
+use crate::{
+    bindings::{Action, Bindings},
+    vkeyboard::{self, Button, Key, VKeyboardComponent},
+    AppState, FontAssets, InputState, Player,
+};
+
+const CHAT_BG: Color = Color::rgba(0., 0., 0., 0.5);
+const CHAT_BTN_TXT: &str = "Chat";
+const CHAT_CLR_GLOBAL: Color = Color::WHITE;
+const CHAT_CLR_INPUT: Color = Color::PINK;
+const CHAT_CLR_ROOM: Color = Color::CYAN;
+const CHAT_CLR_SERVER: Color = Color::YELLOW;
+const CHAT_LINES: usize = 8;
+const CHAT_PAD: f32 = 5.0; // Px
+const CHAT_SZE: f32 = 16.0; // Font Size
+const CHAT_WIDTH: f32 = 50.0; // Percent of the window
+
+pub struct ChatPlugin;
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        for state in [AppState::Game, AppState::Menu] {
+            app.add_system_set(SystemSet::on_enter(state.clone()).with_system(setup))
+                .add_system_set(SystemSet::on_exit(state.clone()).with_system(cleanup))
+                .add_system_set(
+                    SystemSet::on_update(state)
+                        .with_system(input_button)
+                        .with_system(input_keyboard.after(InputState::Keyboard))
+                        .with_system(input_vkeyboard)
+                        .with_system(update_text),
+                );
+        }
+
+        app.add_system(chat_message).insert_resource(Chat {
+            channel: ChatChannel::Global,
+            input: String::new(),
+            lines: VecDeque::new(),
+            open: false,
+            visible: true,
+        });
+    }
+}
+
+/// Chat history and input. Other input systems of the screen ignore the
+/// keyboard and mouse while the chat is `open`.
+pub struct Chat {
+    channel: ChatChannel,
+    input: String,
+    lines: VecDeque<(ChatChannel, String, String)>,
+    pub open: bool,
+    visible: bool,
+}
+
+impl Chat {
+    fn push(&mut self, channel: ChatChannel, name: String, text: String) {
+        self.lines.push_back((channel, name, text));
+        if self.lines.len() > CHAT_LINES {
+            self.lines.pop_front();
+        }
+    }
+}
+
+#[derive(Component)]
+struct ChatButton;
+
+#[derive(Component)]
+struct ChatComponent;
+
+#[derive(Component)]
+struct ChatText;
+
+fn chat_message(
+    mut chat: ResMut<Chat>,
+    mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
+) {
+    for event in event_reader.iter() {
+        match event {
+            MessageEvent(_, Protocol::AnnounceMsg(msg)) => {
+                chat.push(ChatChannel::Server, String::new(), (*msg.text).clone());
+            }
+            MessageEvent(_, Protocol::ChatMsg(msg)) => {
+                chat.push(*msg.channel, (*msg.name).clone(), (*msg.text).clone());
+            }
+            _ => (),
+        }
+    }
+}
+
+fn cleanup(
+    mut chat: ResMut<Chat>,
+    mut commands: Commands,
+    query: Query<Entity, With<ChatComponent>>,
+) {
+    chat.open = false;
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn close(chat: &mut Chat, commands: &mut Commands, keys: &Query<Entity, With<VKeyboardComponent>>) {
+    chat.input.clear();
+    chat.open = false;
+    vkeyboard::hide(commands, keys);
+}
+
+fn input_button(
+    mut chat: ResMut<Chat>,
+    mut commands: Commands,
+    mut event_writer: EventWriter<Button>,
+    input_state: Res<InputState>,
+    keys: Query<Entity, With<VKeyboardComponent>>,
+    query: Query<&Interaction, (Changed<Interaction>, With<ChatButton>)>,
+) {
+    for interaction in query.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        if chat.open {
+            close(&mut chat, &mut commands, &keys);
+            continue;
+        }
+
+        chat.open = true;
+        chat.visible = true;
+        if vec![InputState::Mouse, InputState::Touch].contains(&input_state) {
+            vkeyboard::show(&mut event_writer);
+        }
+    }
+}
+
+fn input_keyboard(
+    bindings: Res<Bindings>,
+    mut chat: ResMut<Chat>,
+    mut client: Client<Protocol, DefaultChannels>,
+    mut commands: Commands,
+    mut input: ResMut<Input<KeyCode>>,
+    mut input_char: EventReader<ReceivedCharacter>,
+    input_state: Res<InputState>,
+    keys: Query<Entity, With<VKeyboardComponent>>,
+    player: Res<Player>,
+) {
+    if !vec![InputState::Keyboard].contains(&input_state) {
+        return;
+    }
+
+    if !chat.open {
+        if bindings.just_pressed(&input, Action::ChatHide) {
+            bindings.reset(&mut input, Action::ChatHide);
+            chat.visible = !chat.visible;
+        }
+
+        if bindings.just_pressed(&input, Action::Chat) {
+            bindings.reset(&mut input, Action::Chat);
+            input_char.iter().for_each(drop);
+            chat.open = true;
+            chat.visible = true;
+        }
+
+        return;
+    }
+
+    if bindings.pressed(&input, Action::Back) {
+        bindings.reset(&mut input, Action::Back);
+        close(&mut chat, &mut commands, &keys);
+        return;
+    }
+
+    if bindings.pressed(&input, Action::Delete) {
+        bindings.reset(&mut input, Action::Delete);
+        chat.input.pop();
+        return;
+    }
+
+    if bindings.pressed(&input, Action::Confirm) {
+        bindings.reset(&mut input, Action::Confirm);
+        send(&mut chat, &mut client, &player);
+        close(&mut chat, &mut commands, &keys);
+        return;
+    }
+
+    if bindings.pressed(&input, Action::Switch) {
+        bindings.reset(&mut input, Action::Switch);
+        chat.channel = match chat.channel {
+            ChatChannel::Global => ChatChannel::Room,
+            _ => ChatChannel::Global,
+        };
+    }
+
+    for e in input_char.iter() {
+        if !e.char.is_control() && chat.input.chars().count() < CHAT_SIZE {
+            chat.input.push(e.char);
+        }
+    }
+}
+
+fn input_vkeyboard(
+    mut chat: ResMut<Chat>,
+    mut client: Client<Protocol, DefaultChannels>,
+    mut commands: Commands,
+    mut event_reader: EventReader<Button>,
+    keys: Query<Entity, With<VKeyboardComponent>>,
+    player: Res<Player>,
+) {
+    for btn in event_reader.iter() {
+        if !chat.open {
+            continue;
+        }
+
+        match btn.key {
+            Key::Backspace => {
+                chat.input.pop();
+            }
+            Key::Escape => close(&mut chat, &mut commands, &keys),
+            Key::Letters | Key::Modifier | Key::Symbols => (),
+            Key::Return => {
+                send(&mut chat, &mut client, &player);
+                close(&mut chat, &mut commands, &keys);
+            }
+            _ => {
+                if chat.input.chars().count() < CHAT_SIZE {
+                    chat.input.push_str(btn.to_string().as_str());
+                }
+            }
+        }
+    }
+}
+
+fn send(chat: &mut Chat, client: &mut Client<Protocol, DefaultChannels>, player: &Player) {
+    if chat.input.trim().is_empty() {
+        return;
+    }
+
+    client.send_message(
+        DefaultChannels::UnorderedReliable,
+        &ChatCmd::new(
+            chat.channel,
+            player.name.clone(),
+            chat.input.clone(),
+            player.token.clone(),
+        ),
+    );
+}
+
+fn setup(mut commands: Commands, fonts: Res<FontAssets>) {
+    let style = TextStyle {
+        color: CHAT_CLR_GLOBAL,
+        font: fonts.regular.clone(),
+        font_size: CHAT_SZE,
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            color: Color::NONE.into(),
+            style: Style {
+                align_items: AlignItems::FlexStart,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::FlexStart,
+                size: Size::new(Val::Percent(CHAT_WIDTH), Val::Percent(100.)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(4. * Vec3::Z),
+            ..Default::default()
+        })
+        .with_children(|p| {
+            p.spawn_bundle(ButtonBundle {
+                color: CHAT_BG.into(),
+                style: Style {
+                    margin: UiRect::all(Val::Px(CHAT_PAD)),
+                    padding: UiRect::all(Val::Px(CHAT_PAD)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|p| {
+                p.spawn_bundle(TextBundle {
+                    text: Text::from_section(CHAT_BTN_TXT, style.clone()),
+                    ..Default::default()
+                });
+            })
+            .insert(ChatButton);
+
+            p.spawn_bundle(TextBundle {
+                style: Style {
+                    margin: UiRect::all(Val::Px(CHAT_PAD)),
+                    ..Default::default()
+                },
+                text: Text::from_section("", style),
+                ..Default::default()
+            })
+            .insert(ChatText);
+        })
+        .insert(ChatComponent);
+}
+
+fn update_text(
+    added: Query<(), Added<ChatText>>,
+    chat: Res<Chat>,
+    mut query: Query<(&mut Text, &mut Visibility), With<ChatText>>,
+) {
+    if !chat.is_changed() && added.is_empty() {
+        return;
+    }
+
+    for (mut text, mut visibility) in query.iter_mut() {
+        visibility.is_visible = chat.visible;
+        let style = text.sections[0].style.clone();
+        let section = |value: String, color: Color| TextSection {
+            value,
+            style: TextStyle {
+                color,
+                ..style.clone()
+            },
+        };
+
+        let mut sections: Vec<TextSection> = chat
+            .lines
+            .iter()
+            .map(|(channel, name, text)| match channel {
+                ChatChannel::Global => section(format!("{}: {}\n", name, text), CHAT_CLR_GLOBAL),
+                ChatChannel::Room => section(format!("{}: {}\n", name, text), CHAT_CLR_ROOM),
+                ChatChannel::Server => section(format!("* {}\n", text), CHAT_CLR_SERVER),
+            })
+            .collect();
+
+        if chat.open {
+            let channel = match chat.channel {
+                ChatChannel::Global => "Global",
+                _ => "Room",
+            };
+
+            sections.push(section(
+                format!("[{}] > {}_", channel, chat.input),
+                CHAT_CLR_INPUT,
+            ));
+        }
+
+        // Keep one section so the style survives an empty history
+        if sections.is_empty() {
+            sections.push(section(String::new(), CHAT_CLR_GLOBAL));
+        }
+
+        text.sections = sections;
+    }
+}
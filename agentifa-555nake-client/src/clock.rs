@@ -0,0 +1,64 @@
+use agentifa_555nake_protocol::protocol::Clock;
+use bevy::{
+    prelude::{App, Plugin, Query, Res, ResMut},
+    time::Time,
+};
+
+const CLOCK_SYNC_TICKS: u64 = 20; // Ticks until the tick duration is measured
+
+pub struct ClockPlugin;
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(update_clock).insert_resource(ServerClock {
+            dur: None,
+            start: None,
+            tick: 0,
+        });
+    }
+}
+
+/// Simulation clock of the server read from the replicated `Clock`. `dur` is
+/// the tick duration in seconds measured since `start`.
+pub struct ServerClock {
+    pub dur: Option<f32>,
+    start: Option<(u64, f64)>,
+    pub tick: u64,
+}
+
+impl ServerClock {
+    /// Seconds the server simulated since `tick`
+    pub fn since(&self, tick: u64) -> Option<f32> {
+        Some(self.tick.checked_sub(tick)? as f32 * self.dur?)
+    }
+}
+
+fn update_clock(clocks: Query<&Clock>, mut server_clock: ResMut<ServerClock>, time: Res<Time>) {
+    let tick = match clocks.iter().next() {
+        Some(clock) => *clock.tick,
+        None if server_clock.start.is_some() => {
+            // Offline, the next connection may go to another server
+            server_clock.dur = None;
+            server_clock.start = None;
+            return;
+        }
+        None => return,
+    };
+
+    if tick == server_clock.tick && server_clock.start.is_some() {
+        return;
+    }
+
+    let now = time.seconds_since_startup();
+    if tick < server_clock.tick || server_clock.start.is_none() {
+        server_clock.dur = None;
+        server_clock.start = Some((tick, now));
+    }
+
+    if let Some((start_tick, start_time)) = server_clock.start {
+        if tick - start_tick >= CLOCK_SYNC_TICKS {
+            server_clock.dur = Some(((now - start_time) / (tick - start_tick) as f64) as f32);
+        }
+    }
+
+    server_clock.tick = tick;
+}
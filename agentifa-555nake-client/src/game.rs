@@ -1,19 +1,20 @@
 use std::collections::HashMap;
 
 use agentifa_555nake_protocol::protocol::{
-    DirCmd, Direction, Food, Head, Name, Position, Protocol, QuitCmd, Score, Segment, StartCmd,
-    GRID_SIZE,
+    Body, BoostCmd, DirCmd, Direction, Food, Head, Name, Position, Protocol, QuitCmd, Score,
+    StartCmd, GRID_SIZE,
 };
 
 use bevy::{
     core_pipeline::clear_color::ClearColor,
-    input::Input,
+    input::{touch::Touches, Input},
     math::{Quat, Vec2, Vec3, Vec4},
     prelude::{
         default, Added, App, BuildChildren, Camera2dBundle, ChangeTrackers, Changed, Color,
         Commands, Component, DespawnRecursiveExt, Entity, EventReader, Handle, KeyCode,
-        MouseButton, NodeBundle, Or, ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut,
-        State, SystemSet, TextBundle, Timer, Transform, UiCameraConfig, With, Without,
+        Local as LocalState, MouseButton, NodeBundle, Or, ParallelSystemDescriptorCoercion, Plugin,
+        Query, Res, ResMut, State, SystemSet, TextBundle, Timer, Transform, UiCameraConfig,
+        Visibility, With, Without,
     },
     sprite::{Sprite, SpriteBundle, SpriteSheetBundle, TextureAtlas, TextureAtlasSprite},
     text::{Text, Text2dBundle, TextAlignment, TextStyle},
@@ -21,14 +22,21 @@ use bevy::{
     ui::{AlignItems, JustifyContent, Size, Style, UiRect, Val},
     window::Windows,
 };
-use bevy_kira_audio::{Audio, AudioControl};
+use bevy_kira_audio::{Audio, AudioChannel, AudioControl};
 use chrono::{offset::Local as LocalTime, Duration, NaiveDate};
 use holiday_de::{DateExt, GermanHoliday};
 use naia_bevy_client::{events::MessageEvent, shared::DefaultChannels, Client};
 use rand::prelude::random;
 
-use crate::{AppState, ImageAssets, InputState, Player, SpriteSheetAssets};
-use crate::{AudioAssets, FontAssets};
+use crate::{AppState, Connection, ImageAssets, InputState, NetState, Player, SpriteSheetAssets};
+use crate::{
+    bindings::{Action, Bindings},
+    chat::Chat,
+    clock::ServerClock,
+    gamepad::{Pad, PadButton},
+    settings::Options,
+    touch_position, AudioAssets, FontAssets, Sfx,
+};
 
 const AUDIO_RATE_FAKTOR: f64 = 0.01;
 const BG_COLOR: Color = Color::rgb(0.5, 0.5, 0.5);
@@ -42,12 +50,22 @@ const HEAD_COLOR_L: f32 = 0.5;
 const HEAD_COLOR_S: f32 = 1.;
 const HEAD_COLOR_SPEED: f32 = 0.01;
 const INVINCIBLE_DUR: f32 = 0.25;
+const MOTION_DUR_MAX: f32 = 1.0; // Seconds
+const MOTION_DUR_MIN: f32 = 0.05; // Seconds
+const MOTION_SNAP: f32 = 1.5; // Blocks
 const NAME_COLOR: Color = Color::rgba(1.0, 0.08, 0.58, 0.5);
 const NAME_FONTSZE: f32 = 0.5;
+const NAME_OFFS: f32 = 1.0; // Blocks
 const NAME_ZIDX: f32 = 2.;
+const PREDICT_MAX: f32 = 1.0; // Blocks
+const RECONNECT_BG: Color = Color::rgba(0., 0., 0., 0.75);
+const RECONNECT_CLR: Color = Color::YELLOW;
+const RECONNECT_SZE: f32 = 30.0; // Font Size
+const RECONNECT_TXT: &str = "Verbindung verloren\nVerbinde neu...";
 const SCOREBAR_COLOR: Color = Color::GRAY;
 const SCORETEXT_COLOR: Color = Color::YELLOW;
 const SEGMENT_ANIM_CNT: usize = 6;
+const SWIPE_MIN: f32 = 30.0; // Px
 
 pub struct GamePlugin;
 impl Plugin for GamePlugin {
@@ -57,19 +75,28 @@ impl Plugin for GamePlugin {
             .add_system_set(
                 SystemSet::on_update(AppState::Game)
                     .with_system(assign_message)
+                    .with_system(input_gamepad.after(InputState::Gamepad))
                     .with_system(input_keyboard.after(InputState::Keyboard))
                     .with_system(input_mouse.after(InputState::Mouse))
+                    .with_system(input_touch.after(InputState::Touch))
                     .with_system(quit_command)
+                    .with_system(reject_message)
                     .with_system(update_audio)
                     .with_system(update_background)
-                    .with_system(update_buttons.after(InputState::Mouse))
+                    .with_system(update_bodies)
+                    .with_system(
+                        update_buttons
+                            .after(InputState::Mouse)
+                            .after(InputState::Touch),
+                    )
                     .with_system(update_dimensions)
                     .with_system(update_foods)
                     .with_system(update_head_color)
                     .with_system(update_head_dir)
                     .with_system(update_heads)
-                    .with_system(update_name_positions)
-                    .with_system(update_names)
+                    .with_system(update_motions)
+                    .with_system(update_names.after(update_motions))
+                    .with_system(update_overlay)
                     .with_system(update_positions)
                     .with_system(update_scales)
                     .with_system(update_scorebar)
@@ -77,15 +104,16 @@ impl Plugin for GamePlugin {
                     .with_system(update_scorecoin)
                     .with_system(update_scores)
                     .with_system(update_scoretext)
-                    .with_system(update_segments)
                     .with_system(update_sheets)
                     .with_system(update_texts),
             )
             .insert_resource(Dimensions::default())
             .insert_resource(Global {
+                bodies: HashMap::new(),
                 names: HashMap::new(),
                 scores: HashMap::new(),
-            });
+            })
+            .insert_resource(Steering::default());
     }
 }
 
@@ -116,7 +144,8 @@ enum Button {
 struct Coin;
 
 struct Global {
-    names: HashMap<Entity, (Entity, usize, usize)>,
+    bodies: HashMap<Entity, Vec<Entity>>,
+    names: HashMap<Entity, Entity>,
     scores: HashMap<Entity, usize>,
 }
 
@@ -131,9 +160,62 @@ struct HeadLocal {
 #[derive(Component)]
 struct Local;
 
+/// Moves a sprite smoothly from one grid cell to the next. Positions are
+/// given in blocks.
+#[derive(Component)]
+struct Motion {
+    duration: f32,
+    elapsed: f32,
+    from: Vec2,
+    tick: u64,
+    to: Vec2,
+    updated: f64,
+}
+
+impl Motion {
+    fn new(position: &Position) -> Self {
+        let to = Vec2::new(*position.x as f32, *position.y as f32);
+        Motion {
+            duration: 0.,
+            elapsed: 0.,
+            from: to,
+            tick: 0,
+            to,
+            updated: 0.,
+        }
+    }
+
+    /// Returns the position to display. If `predict` is given, the sprite
+    /// keeps moving in that direction after it reached its target, up to
+    /// `PREDICT_MAX` blocks, until the next authoritative position arrives.
+    fn current(&self, predict: Option<Direction>) -> Vec2 {
+        let t = match self.duration > 0. {
+            true => self.elapsed / self.duration,
+            false => 1.,
+        };
+
+        let current = self.from.lerp(self.to, t.min(1.));
+        if let Some(dir) = predict {
+            let ahead = current + direction(dir) * (t - 1.).clamp(0., PREDICT_MAX);
+            let grid = (GRID_SIZE - 1) as f32;
+            if ahead.cmpge(Vec2::ZERO).all() && ahead.cmple(Vec2::splat(grid)).all() {
+                return ahead;
+            }
+        }
+
+        current
+    }
+}
+
+#[derive(Component)]
+struct NameLabel;
+
 #[derive(Component)]
 struct Own;
 
+#[derive(Component)]
+struct ReconnectOverlay;
+
 #[derive(Component)]
 struct Remote;
 
@@ -146,13 +228,22 @@ struct ScoreBar;
 #[derive(Component)]
 struct ScoreBarContainer;
 
+/// The direction that was last sent to the server, used to predict the own
+/// head before the server confirms the turn.
+#[derive(Default)]
+struct Steering {
+    dir: Option<Direction>,
+}
+
 fn assign_message(
     client: Client<Protocol, DefaultChannels>,
     mut commands: Commands,
+    mut connection: ResMut<Connection>,
     mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
 ) {
     for event in event_reader.iter() {
         if let MessageEvent(_, Protocol::AssignMsg(msg)) = event {
+            connection.session = Some((*msg.session).clone());
             commands
                 .entity(msg.entity.get(&client).unwrap())
                 .insert(Own);
@@ -163,11 +254,16 @@ fn assign_message(
 fn cleanup(
     mut client: Client<Protocol, DefaultChannels>,
     mut commands: Commands,
+    mut connection: ResMut<Connection>,
     mut global: ResMut<Global>,
     local: Query<Entity, With<Local>>,
     remote: Query<Entity, With<Remote>>,
+    mut steering: ResMut<Steering>,
 ) {
     client.send_message(DefaultChannels::UnorderedReliable, &QuitCmd::new());
+    connection.session = None;
+    steering.dir = None;
+    global.bodies.clear();
     global.names.clear();
     global.scores.clear();
     for entity in local.iter() {
@@ -179,69 +275,106 @@ fn cleanup(
             .entity(entity)
             .remove::<Animation>()
             .remove::<HeadLocal>()
+            .remove::<Motion>()
             .remove::<Remote>()
             .remove_bundle::<SpriteSheetBundle>();
     }
 }
 
-fn input_keyboard(
+fn direction(dir: Direction) -> Vec2 {
+    match dir {
+        Direction::Down => Vec2::NEG_Y,
+        Direction::Left => Vec2::NEG_X,
+        Direction::Right => Vec2::X,
+        Direction::Up => Vec2::Y,
+    }
+}
+
+fn input_gamepad(
     mut app_state: ResMut<State<AppState>>,
+    chat: Res<Chat>,
     mut client: Client<Protocol, DefaultChannels>,
-    mut input: ResMut<Input<KeyCode>>,
     input_state: Res<InputState>,
+    pad: Res<Pad>,
+    mut steering: ResMut<Steering>,
 ) {
-    if !vec![InputState::Keyboard].contains(&input_state) {
+    if chat.open || !vec![InputState::Gamepad].contains(&input_state) {
         return;
     }
 
-    if input.pressed(KeyCode::Escape) {
-        input.release(KeyCode::Escape);
+    if pad.just_pressed(PadButton::Start) {
         app_state.set(AppState::Menu).unwrap();
         return;
     }
 
-    if input.pressed(KeyCode::Down) {
-        input.release(KeyCode::Down);
-        client.send_message(
-            DefaultChannels::UnorderedReliable,
-            &DirCmd::new(Direction::Down),
-        );
+    if pad.just_pressed(PadButton::South) {
+        client.send_message(DefaultChannels::UnorderedReliable, &BoostCmd::new());
     }
-    if input.pressed(KeyCode::Left) {
-        input.release(KeyCode::Left);
-        client.send_message(
-            DefaultChannels::UnorderedReliable,
-            &DirCmd::new(Direction::Left),
-        );
+
+    for (btn, dir) in [
+        (PadButton::Down, Direction::Down),
+        (PadButton::Left, Direction::Left),
+        (PadButton::Right, Direction::Right),
+        (PadButton::Up, Direction::Up),
+    ] {
+        if pad.just_pressed(btn) {
+            steer(&mut client, &mut steering, dir);
+        }
     }
-    if input.pressed(KeyCode::Right) {
-        input.release(KeyCode::Right);
-        client.send_message(
-            DefaultChannels::UnorderedReliable,
-            &DirCmd::new(Direction::Right),
-        );
+}
+
+fn input_keyboard(
+    mut app_state: ResMut<State<AppState>>,
+    bindings: Res<Bindings>,
+    chat: Res<Chat>,
+    mut client: Client<Protocol, DefaultChannels>,
+    mut input: ResMut<Input<KeyCode>>,
+    input_state: Res<InputState>,
+    mut steering: ResMut<Steering>,
+) {
+    if chat.open || !vec![InputState::Keyboard].contains(&input_state) {
+        return;
     }
-    if input.pressed(KeyCode::Up) {
-        input.release(KeyCode::Up);
-        client.send_message(
-            DefaultChannels::UnorderedReliable,
-            &DirCmd::new(Direction::Up),
-        );
+
+    if bindings.take(&mut input, Action::Back) {
+        app_state.set(AppState::Menu).unwrap();
+        return;
+    }
+
+    if bindings.just_pressed(&input, Action::Boost) {
+        client.send_message(DefaultChannels::UnorderedReliable, &BoostCmd::new());
+    }
+
+    for dir in [
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+        Direction::Up,
+    ] {
+        if bindings.take(&mut input, Action::Steer(dir)) {
+            steer(&mut client, &mut steering, dir);
+        }
     }
 }
 
 fn input_mouse(
     mut app_state: ResMut<State<AppState>>,
     buttons: Query<(&Button, &Transform)>,
+    chat: Res<Chat>,
     mut client: Client<Protocol, DefaultChannels>,
     input: Res<Input<MouseButton>>,
     input_state: Res<InputState>,
+    mut steering: ResMut<Steering>,
     windows: Res<Windows>,
 ) {
-    if !vec![InputState::Mouse].contains(&input_state) {
+    if chat.open || !vec![InputState::Mouse].contains(&input_state) {
         return;
     }
 
+    if input.just_pressed(MouseButton::Right) {
+        client.send_message(DefaultChannels::UnorderedReliable, &BoostCmd::new());
+    }
+
     if input.just_pressed(MouseButton::Left) {
         let wnd = windows.get_primary().unwrap();
         if let Some(mut cursor) = wnd.cursor_position() {
@@ -262,8 +395,7 @@ fn input_mouse(
                 if contains(cursor, rect) {
                     match *btn {
                         Button::Escape => app_state.set(AppState::Menu).unwrap(),
-                        Button::Direction(dir) => client
-                            .send_message(DefaultChannels::UnorderedReliable, &DirCmd::new(dir)),
+                        Button::Direction(dir) => steer(&mut client, &mut steering, dir),
                     }
                 }
             }
@@ -271,6 +403,79 @@ fn input_mouse(
     }
 }
 
+/// Swipes on the board steer, taps press the buttons. Every finger is
+/// tracked on its own, so a second touch does not disturb a swipe.
+fn input_touch(
+    mut app_state: ResMut<State<AppState>>,
+    buttons: Query<(&Button, &Transform)>,
+    chat: Res<Chat>,
+    mut client: Client<Protocol, DefaultChannels>,
+    input_state: Res<InputState>,
+    mut steering: ResMut<Steering>,
+    mut swipes: LocalState<HashMap<u64, (Vec2, bool)>>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+) {
+    if chat.open || !vec![InputState::Touch].contains(&input_state) {
+        swipes.clear();
+        return;
+    }
+
+    let wnd = windows.get_primary().unwrap();
+    for touch in touches.iter_just_pressed() {
+        swipes.insert(touch.id(), (touch_position(wnd, touch.position()), false));
+    }
+
+    for touch in touches.iter() {
+        if let Some((origin, swiped)) = swipes.get_mut(&touch.id()) {
+            let delta = touch_position(wnd, touch.position()) - *origin;
+            if delta.length() < SWIPE_MIN {
+                continue;
+            }
+
+            let dir = match delta.x.abs() > delta.y.abs() {
+                true if delta.x > 0. => Direction::Right,
+                true => Direction::Left,
+                false if delta.y > 0. => Direction::Up,
+                false => Direction::Down,
+            };
+
+            // The next swipe starts here, so one gesture can turn twice
+            steer(&mut client, &mut steering, dir);
+            *origin += delta;
+            *swiped = true;
+        }
+    }
+
+    for touch in touches.iter_just_cancelled() {
+        swipes.remove(&touch.id());
+    }
+
+    let contains = |p: Vec2, tf: &Transform| {
+        let offs = 0.5 * tf.scale;
+        p.x > tf.translation.x - offs.x
+            && p.x < tf.translation.x + offs.x
+            && p.y > tf.translation.y - offs.y
+            && p.y < tf.translation.y + offs.y
+    };
+
+    for touch in touches.iter_just_released() {
+        if !matches!(swipes.remove(&touch.id()), Some((_, false))) {
+            continue;
+        }
+
+        let pos = touch_position(wnd, touch.position());
+        match buttons.iter().find(|(_, tf)| contains(pos, *tf)) {
+            Some((Button::Escape, _)) => {
+                app_state.set(AppState::Menu).unwrap();
+                return;
+            }
+            Some((Button::Direction(dir), _)) => steer(&mut client, &mut steering, *dir),
+            None => (),
+        }
+    }
+}
+
 fn quit_command(
     mut app_state: ResMut<State<AppState>>,
     mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
@@ -282,6 +487,19 @@ fn quit_command(
     }
 }
 
+fn reject_message(
+    mut app_state: ResMut<State<AppState>>,
+    mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
+    mut player: ResMut<Player>,
+) {
+    for event in event_reader.iter() {
+        if let MessageEvent(_, Protocol::RejectMsg(msg)) = event {
+            player.reject = Some(*msg.reason);
+            app_state.set(AppState::Register).unwrap();
+        }
+    }
+}
+
 fn setup(
     audio: Res<Audio>,
     mut clear: ResMut<ClearColor>,
@@ -289,7 +507,7 @@ fn setup(
     mut commands: Commands,
     fonts: Res<FontAssets>,
     images: Res<ImageAssets>,
-    player: Res<Player>,
+    mut player: ResMut<Player>,
     sheets: Res<SpriteSheetAssets>,
     sounds: Res<AudioAssets>,
 ) {
@@ -426,6 +644,39 @@ fn setup(
         .insert(Button::Escape)
         .insert(Local);
 
+    // spawn reconnect overlay
+    commands
+        .spawn_bundle(NodeBundle {
+            color: RECONNECT_BG.into(),
+            style: Style {
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                ..default()
+            },
+            transform: Transform::from_translation(3. * Vec3::Z),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .with_children(|p| {
+            p.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    RECONNECT_TXT,
+                    TextStyle {
+                        color: RECONNECT_CLR,
+                        font: fonts.bold.clone(),
+                        font_size: RECONNECT_SZE,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                visibility: Visibility { is_visible: false },
+                ..default()
+            })
+            .insert(ReconnectOverlay);
+        })
+        .insert(Local)
+        .insert(ReconnectOverlay);
+
     // insert segment resource
     commands.insert_resource(match check_date(LocalTime::now().naive_local().date()) {
         Some(GermanHoliday::Allerheiligen) => sheets.pumpkin.clone(),
@@ -439,13 +690,35 @@ fn setup(
     });
 
     // spawn player
+    player.reject = None;
     client.send_message(
         DefaultChannels::UnorderedReliable,
-        &StartCmd::new(player.name.clone()),
+        &StartCmd::new(
+            player.name.clone(),
+            player.room.clone(),
+            player.token.clone(),
+        ),
     );
 }
 
-fn update_audio(audio: Res<Audio>, query: Query<&Score, (Changed<Score>, With<Own>)>) {
+fn steer(
+    client: &mut Client<Protocol, DefaultChannels>,
+    steering: &mut Steering,
+    dir: Direction,
+) {
+    steering.dir = Some(dir);
+    client.send_message(DefaultChannels::UnorderedReliable, &DirCmd::new(dir));
+}
+
+fn update_audio(
+    audio: Res<Audio>,
+    options: Res<Options>,
+    query: Query<&Score, (Changed<Score>, With<Own>)>,
+) {
+    if !options.tempo {
+        return;
+    }
+
     for score in query.iter() {
         audio.set_playback_rate(1. + AUDIO_RATE_FAKTOR * *score.level as f64);
     }
@@ -462,6 +735,72 @@ fn update_background(
     }
 }
 
+/// Rebuilds the segment sprites of each snake from the `Body` of its head.
+fn update_bodies(
+    mut commands: Commands,
+    mut global: ResMut<Global>,
+    heads: Query<(ChangeTrackers<Body>, Entity, &Body, &Position), With<Head>>,
+    mut segments: Query<&mut Position, Without<Head>>,
+    sheet: Res<Handle<TextureAtlas>>,
+) {
+    global.bodies.retain(|k, v| {
+        let retain = heads.get(*k).is_ok();
+        if !retain {
+            for entity in v.iter() {
+                commands.entity(*entity).despawn_recursive();
+            }
+        }
+
+        retain
+    });
+
+    for (tracker, entity, body, position) in heads.iter() {
+        if !tracker.is_changed() {
+            continue;
+        }
+
+        let sprites = global.bodies.entry(entity).or_default();
+        let mut cell = Position::new(*position.x, *position.y);
+        for (i, dir) in body.dirs.iter().enumerate() {
+            cell.step(*dir);
+            if let Some(sprite) = sprites.get(i) {
+                if let Ok(mut position) = segments.get_mut(*sprite) {
+                    *position.x = *cell.x;
+                    *position.y = *cell.y;
+                }
+
+                continue;
+            }
+
+            let sprite = commands
+                .spawn()
+                .insert(Animation {
+                    count: SEGMENT_ANIM_CNT,
+                })
+                .insert(Local)
+                .insert(Motion::new(&cell))
+                .insert(Position::new(*cell.x, *cell.y))
+                .insert_bundle(SpriteSheetBundle {
+                    sprite: TextureAtlasSprite {
+                        index: 0,
+                        custom_size: Some(Vec2::ONE),
+                        ..default()
+                    },
+                    texture_atlas: sheet.clone(),
+                    transform: Transform::from_translation(Vec3::Z),
+                    ..default()
+                })
+                .id();
+
+            sprites.push(sprite);
+        }
+
+        for sprite in sprites.drain(body.dirs.len()..) {
+            commands.entity(sprite).despawn_recursive();
+        }
+    }
+}
+
 fn update_buttons(
     mut buttons: Query<(&Button, &mut TextureAtlasSprite, &mut Transform)>,
     dimensions: Res<Dimensions>,
@@ -495,8 +834,8 @@ fn update_buttons(
 
         if input_state.is_changed() {
             tex.color = match *input_state {
-                InputState::Keyboard => Color::NONE,
-                InputState::Mouse => BTN_COLOR,
+                InputState::Gamepad | InputState::Keyboard => Color::NONE,
+                InputState::Mouse | InputState::Touch => BTN_COLOR,
             };
         }
     }
@@ -595,6 +934,7 @@ fn update_heads(
             .insert(Animation {
                 count: HEAD_ANIM_CNT,
             })
+            .insert(Motion::new(position))
             .insert(Remote)
             .insert(HeadLocal {
                 color_dst: HEAD_COLOR,
@@ -616,7 +956,7 @@ fn update_heads(
         let name = commands
             .spawn()
             .insert(Local)
-            .insert(Position::new(*position.x, *position.y - 1))
+            .insert(NameLabel)
             .insert_bundle(Text2dBundle {
                 text: Text::from_section(
                     (*name.text).clone(),
@@ -632,53 +972,115 @@ fn update_heads(
             })
             .id();
 
-        global
-            .names
-            .insert(entity, (name, *position.x, *position.y));
+        global.names.insert(entity, name);
+    }
+}
+
+fn update_motions(
+    dimensions: Res<Dimensions>,
+    mut query: Query<(
+        ChangeTrackers<Position>,
+        Option<(&Head, ChangeTrackers<Head>)>,
+        &mut Motion,
+        Option<&Own>,
+        &Position,
+        &mut Transform,
+    )>,
+    server_clock: Res<ServerClock>,
+    mut steering: ResMut<Steering>,
+    time: Res<Time>,
+) {
+    let grid = Vec2::new(
+        dimensions.blk * (GRID_SIZE as f32),
+        dimensions.blk * ((GRID_SIZE + 1) as f32),
+    );
+
+    let now = time.seconds_since_startup();
+    let offs = 0.5 * (grid - dimensions.blk);
+    for (tracker, head, mut motion, own, pos, mut tf) in query.iter_mut() {
+        let predict = match (head, own) {
+            (Some((head, head_tracker)), Some(_)) => {
+                if head_tracker.is_changed() {
+                    steering.dir = None;
+                }
+
+                match *head.running || steering.dir.is_some() {
+                    true => Some(steering.dir.unwrap_or(*head.dir)),
+                    false => None,
+                }
+            }
+            _ => None,
+        };
+
+        let to = Vec2::new(*pos.x as f32, *pos.y as f32);
+        if tracker.is_changed() && to != motion.to {
+            let current = motion.current(predict);
+            motion.duration = match head {
+                Some((head, _)) => *head.step as f32 / 1000.,
+                None => server_clock
+                    .since(motion.tick)
+                    .unwrap_or((now - motion.updated) as f32)
+                    .clamp(MOTION_DUR_MIN, MOTION_DUR_MAX),
+            };
+
+            motion.elapsed = 0.;
+            motion.from = match current.distance(to) > MOTION_SNAP {
+                true => to,
+                false => current,
+            };
+
+            motion.tick = server_clock.tick;
+            motion.to = to;
+            motion.updated = now;
+        } else {
+            motion.elapsed += time.delta_seconds();
+        }
+
+        tf.translation =
+            (motion.current(predict) * dimensions.blk - offs).extend(tf.translation.z);
     }
 }
 
-fn update_name_positions(
+fn update_names(
     mut commands: Commands,
+    dimensions: Res<Dimensions>,
     mut global: ResMut<Global>,
-    query: Query<(ChangeTrackers<Position>, Entity, &Position), With<Name>>,
+    heads: Query<&Transform, (With<Head>, Without<NameLabel>)>,
+    mut labels: Query<(&mut Transform, &mut Visibility), With<NameLabel>>,
+    options: Res<Options>,
 ) {
-    global.names.retain(|k, (v, _, _)| {
-        let retain = query.get(*k).is_ok();
+    global.names.retain(|k, v| {
+        let retain = heads.get(*k).is_ok();
         if !retain {
             commands.entity(*v).despawn_recursive();
         }
 
         retain
     });
-    for (tracker, entity, position) in query.iter() {
-        if !tracker.is_changed() {
-            continue;
-        }
 
-        if let Some((_, x, y)) = global.names.get_mut(&entity) {
-            *x = *position.x;
-            *y = *position.y + 1;
+    for (head, label) in global.names.iter() {
+        let pair = (heads.get(*head), labels.get_mut(*label));
+        if let (Ok(head), Ok((mut tf, mut visibility))) = pair {
+            visibility.is_visible = options.names;
+            tf.translation.x = head.translation.x;
+            tf.translation.y = head.translation.y + NAME_OFFS * dimensions.blk;
         }
     }
 }
 
-fn update_names(global: Res<Global>, mut query: Query<&mut Position, With<Text>>) {
-    if !global.is_changed() {
-        return;
-    }
-
-    for (_, (entity, x, y)) in global.names.iter() {
-        if let Ok(mut position) = query.get_mut(*entity) {
-            *position.x = *x;
-            *position.y = *y;
-        }
+fn update_overlay(
+    net_state: Res<State<NetState>>,
+    mut query: Query<&mut Visibility, With<ReconnectOverlay>>,
+) {
+    let offline = vec![NetState::Offline].contains(net_state.current());
+    for mut visibility in query.iter_mut() {
+        visibility.is_visible = offline;
     }
 }
 
 fn update_positions(
     dimensions: Res<Dimensions>,
-    mut positions: Query<(ChangeTrackers<Position>, &Position, &mut Transform)>,
+    mut positions: Query<(ChangeTrackers<Position>, &Position, &mut Transform), Without<Motion>>,
 ) {
     for (tracker, pos, mut tf) in positions.iter_mut() {
         if dimensions.is_changed() || tracker.is_changed() {
@@ -738,7 +1140,7 @@ fn update_scorecoin(mut coins: Query<&mut Style, With<Coin>>, dimensions: Res<Di
 }
 
 fn update_scores(
-    audio: Res<Audio>,
+    audio: Res<AudioChannel<Sfx>>,
     query: Query<(ChangeTrackers<Score>, Entity, &Score)>,
     mut global: ResMut<Global>,
     sounds: Res<AudioAssets>,
@@ -775,35 +1177,6 @@ fn update_scoretext(
     }
 }
 
-fn update_segments(
-    mut commands: Commands,
-    query: Query<(Entity, &Segment), Without<Remote>>,
-    sheet: Res<Handle<TextureAtlas>>,
-) {
-    for (entity, segment) in query.iter() {
-        if !*segment.synced {
-            continue;
-        }
-
-        commands
-            .entity(entity)
-            .insert(Animation {
-                count: SEGMENT_ANIM_CNT,
-            })
-            .insert(Remote)
-            .insert_bundle(SpriteSheetBundle {
-                sprite: TextureAtlasSprite {
-                    index: 0,
-                    custom_size: Some(Vec2::ONE),
-                    ..default()
-                },
-                texture_atlas: sheet.clone(),
-                transform: Transform::from_translation(Vec3::Z),
-                ..default()
-            });
-    }
-}
-
 fn update_sheets(mut query: Query<(&Animation, &mut TextureAtlasSprite)>, time: Res<Time>) {
     let cnt = 10.0 * time.seconds_since_startup();
     for (a, mut sheet) in query.iter_mut() {
@@ -811,7 +1184,7 @@ fn update_sheets(mut query: Query<(&Animation, &mut TextureAtlasSprite)>, time:
     }
 }
 
-fn update_texts(dimensions: Res<Dimensions>, mut query: Query<&mut Text, With<Position>>) {
+fn update_texts(dimensions: Res<Dimensions>, mut query: Query<&mut Text, With<NameLabel>>) {
     if !dimensions.is_changed() {
         return;
     }
@@ -1,7 +1,7 @@
 use agentifa_555nake_protocol::protocol::{HighScore, HighScoreRank};
 use bevy::{
     core_pipeline::clear_color::ClearColor,
-    input::Input,
+    input::{touch::Touches, Input},
     math::{Vec2, Vec3},
     prelude::{
         App, BuildChildren, Camera2dBundle, Color, Commands, Component, DespawnRecursiveExt,
@@ -18,7 +18,11 @@ use bevy::{
 };
 use bevy_kira_audio::{Audio, AudioControl};
 
-use crate::{AppState, ImageAssets, InputState, SpriteSheetAssets};
+use crate::{
+    bindings::{Action, Bindings},
+    gamepad::{Pad, PadButton},
+    touch_position, AppState, ImageAssets, InputState, SpriteSheetAssets,
+};
 use crate::{AudioAssets, FontAssets};
 
 const BTN_COLOR: Color = Color::rgba(1., 1., 1., 0.5);
@@ -40,8 +44,10 @@ impl Plugin for GameOverPlugin {
             .add_system_set(SystemSet::on_exit(AppState::Gameover).with_system(cleanup))
             .add_system_set(
                 SystemSet::on_update(AppState::Gameover)
+                    .with_system(input_gamepad.after(InputState::Gamepad))
                     .with_system(input_keyboard.after(InputState::Keyboard))
                     .with_system(input_mouse.after(InputState::Mouse))
+                    .with_system(input_touch.after(InputState::Touch))
                     .with_system(insert_entries)
                     .with_system(update_background)
                     .with_system(update_buttons)
@@ -86,8 +92,23 @@ fn cleanup(
     }
 }
 
+fn input_gamepad(
+    mut app_state: ResMut<State<AppState>>,
+    input_state: Res<InputState>,
+    pad: Res<Pad>,
+) {
+    if !vec![InputState::Gamepad].contains(&input_state) {
+        return;
+    }
+
+    if pad.just_pressed(PadButton::East) || pad.just_pressed(PadButton::Start) {
+        app_state.set(AppState::Menu).unwrap();
+    }
+}
+
 fn input_keyboard(
     mut app_state: ResMut<State<AppState>>,
+    bindings: Res<Bindings>,
     mut input: ResMut<Input<KeyCode>>,
     input_state: Res<InputState>,
 ) {
@@ -95,8 +116,7 @@ fn input_keyboard(
         return;
     }
 
-    if input.pressed(KeyCode::Escape) {
-        input.release(KeyCode::Escape);
+    if bindings.take(&mut input, Action::Back) {
         app_state.set(AppState::Menu).unwrap();
     }
 }
@@ -134,6 +154,33 @@ fn input_mouse(
     }
 }
 
+fn input_touch(
+    mut app_state: ResMut<State<AppState>>,
+    buttons: Query<&Transform, With<Button>>,
+    input_state: Res<InputState>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+) {
+    if !vec![InputState::Touch].contains(&input_state) {
+        return;
+    }
+
+    let wnd = windows.get_primary().unwrap();
+    let tf = buttons.get_single().unwrap();
+    let offs = 0.5 * tf.scale;
+    for touch in touches.iter_just_released() {
+        let pos = touch_position(wnd, touch.position());
+        if pos.x > tf.translation.x - offs.x
+            && pos.x < tf.translation.x + offs.x
+            && pos.y > tf.translation.y - offs.y
+            && pos.y < tf.translation.y + offs.y
+        {
+            app_state.set(AppState::Menu).unwrap();
+            return;
+        }
+    }
+}
+
 fn insert_entries(
     mut commands: Commands,
     fonts: Res<FontAssets>,
@@ -349,8 +396,8 @@ fn update_buttons(
     let (mut tex, mut tf) = buttons.single_mut();
 
     tex.color = match *input_state {
-        InputState::Keyboard => Color::NONE,
-        InputState::Mouse => BTN_COLOR,
+        InputState::Gamepad | InputState::Keyboard => Color::NONE,
+        InputState::Mouse | InputState::Touch => BTN_COLOR,
     };
 
     tf.scale = Vec2::splat(2. * blk).extend(tf.scale.z);
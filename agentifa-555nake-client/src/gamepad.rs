@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use bevy::input::{
+    gamepad::{GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, Gamepads},
+    Axis, Input,
+};
+
+const STICK_THRESHOLD: f32 = 0.5;
+
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+pub enum PadButton {
+    Down,
+    East,
+    Left,
+    North,
+    Right,
+    South,
+    Start,
+    Up,
+    West,
+}
+
+/// Buttons held on any connected gamepad. The left stick counts as D-pad.
+#[derive(Default)]
+pub struct Pad {
+    pressed: HashSet<PadButton>,
+    previous: HashSet<PadButton>,
+}
+
+impl Pad {
+    pub fn any_just_pressed(&self) -> bool {
+        self.pressed.difference(&self.previous).count() > 0
+    }
+
+    pub fn just_pressed(&self, btn: PadButton) -> bool {
+        self.pressed.contains(&btn) && !self.previous.contains(&btn)
+    }
+
+    pub fn update(
+        &mut self,
+        axes: &Axis<GamepadAxis>,
+        buttons: &Input<GamepadButton>,
+        gamepads: &Gamepads,
+    ) {
+        self.previous = std::mem::take(&mut self.pressed);
+        for gamepad in gamepads.iter() {
+            for (btn, typ) in [
+                (PadButton::Down, GamepadButtonType::DPadDown),
+                (PadButton::East, GamepadButtonType::East),
+                (PadButton::Left, GamepadButtonType::DPadLeft),
+                (PadButton::North, GamepadButtonType::North),
+                (PadButton::Right, GamepadButtonType::DPadRight),
+                (PadButton::South, GamepadButtonType::South),
+                (PadButton::Start, GamepadButtonType::Start),
+                (PadButton::Up, GamepadButtonType::DPadUp),
+                (PadButton::West, GamepadButtonType::West),
+            ] {
+                if buttons.pressed(GamepadButton(*gamepad, typ)) {
+                    self.pressed.insert(btn);
+                }
+            }
+
+            let x = axes
+                .get(GamepadAxis(*gamepad, GamepadAxisType::LeftStickX))
+                .unwrap_or_default();
+
+            let y = axes
+                .get(GamepadAxis(*gamepad, GamepadAxisType::LeftStickY))
+                .unwrap_or_default();
+
+            // Only the dominant axis of the stick counts
+            if x.abs() > y.abs() && x.abs() > STICK_THRESHOLD {
+                self.pressed.insert(match x > 0. {
+                    true => PadButton::Right,
+                    false => PadButton::Left,
+                });
+            } else if y.abs() > STICK_THRESHOLD {
+                self.pressed.insert(match y > 0. {
+                    true => PadButton::Up,
+                    false => PadButton::Down,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::{HashMap, VecDeque};
+
+use agentifa_555nake_protocol::protocol::{PingCmd, Protocol};
+use bevy::{
+    math::Vec3,
+    prelude::{
+        App, BuildChildren, Color, Commands, Component, DespawnRecursiveExt, Entity, EventReader,
+        NodeBundle, Plugin, Query, Res, ResMut, State, SystemSet, TextBundle, Timer, Transform,
+        With,
+    },
+    text::{Text, TextStyle},
+    time::Time,
+    ui::{AlignItems, FlexDirection, JustifyContent, Size, Style, UiRect, Val},
+};
+use naia_bevy_client::{events::MessageEvent, shared::DefaultChannels, Client};
+
+use crate::{clock::ServerClock, AppState, FontAssets, NetState};
+
+const HUD_CLR: Color = Color::rgba(1., 1., 1., 0.75);
+const HUD_PAD: f32 = 5.0; // Px
+const HUD_SZE: f32 = 16.0; // Font Size
+const PING_DUR: f32 = 1.0; // Seconds
+const PING_DUR_MAX: f64 = 3.0; // Seconds until a ping counts as lost
+const PING_WINDOW: usize = 20; // Pings used for the packet loss
+const RTT_SMOOTHING: f32 = 0.2;
+
+pub struct HudPlugin;
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Game).with_system(setup))
+            .add_system_set(SystemSet::on_exit(AppState::Game).with_system(cleanup))
+            .add_system_set(SystemSet::on_update(AppState::Game).with_system(update_hud))
+            .add_system(ping_message)
+            .add_system(update_pings)
+            .insert_resource(Pings {
+                id: 0,
+                pending: HashMap::new(),
+                results: VecDeque::new(),
+                rtt: None,
+                timer: Timer::from_seconds(PING_DUR, true),
+            });
+    }
+}
+
+#[derive(Component)]
+struct HudComponent;
+
+#[derive(Component)]
+struct HudText;
+
+/// Round trip time and packet loss measured with `PingCmd`s sent over the
+/// unreliable channel.
+struct Pings {
+    id: u32,
+    pending: HashMap<u32, f64>,
+    results: VecDeque<bool>,
+    rtt: Option<f32>,
+    timer: Timer,
+}
+
+impl Pings {
+    fn loss(&self) -> f32 {
+        match self.results.len() {
+            0 => 0.,
+            len => self.results.iter().filter(|r| !**r).count() as f32 / len as f32,
+        }
+    }
+
+    fn push(&mut self, received: bool) {
+        self.results.push_back(received);
+        if self.results.len() > PING_WINDOW {
+            self.results.pop_front();
+        }
+    }
+}
+
+fn cleanup(mut commands: Commands, query: Query<Entity, With<HudComponent>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn ping_message(
+    mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
+    mut pings: ResMut<Pings>,
+    time: Res<Time>,
+) {
+    for event in event_reader.iter() {
+        if let MessageEvent(_, Protocol::PingMsg(msg)) = event {
+            if let Some(sent) = pings.pending.remove(&*msg.id) {
+                let sample = 1000. * (time.seconds_since_startup() - sent) as f32;
+                pings.rtt = Some(match pings.rtt {
+                    Some(rtt) => rtt + RTT_SMOOTHING * (sample - rtt),
+                    None => sample,
+                });
+
+                pings.push(true);
+            }
+        }
+    }
+}
+
+fn setup(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            color: Color::NONE.into(),
+            style: Style {
+                align_items: AlignItems::FlexEnd,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::FlexStart,
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(3. * Vec3::Z),
+            ..Default::default()
+        })
+        .with_children(|p| {
+            p.spawn_bundle(TextBundle {
+                style: Style {
+                    margin: UiRect::all(Val::Px(HUD_PAD)),
+                    ..Default::default()
+                },
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        color: HUD_CLR,
+                        font: fonts.regular.clone(),
+                        font_size: HUD_SZE,
+                    },
+                ),
+                ..Default::default()
+            })
+            .insert(HudText);
+        })
+        .insert(HudComponent);
+}
+
+fn update_hud(
+    pings: Res<Pings>,
+    mut query: Query<&mut Text, With<HudText>>,
+    server_clock: Res<ServerClock>,
+) {
+    if !pings.is_changed() && !server_clock.is_changed() {
+        return;
+    }
+
+    let rtt = match pings.rtt {
+        Some(rtt) => format!("{:.0} ms", rtt),
+        None => "-".to_string(),
+    };
+
+    for mut text in query.iter_mut() {
+        text.sections[0].value = format!(
+            "Tick {} | RTT {} | Loss {:.0}%",
+            server_clock.tick,
+            rtt,
+            100. * pings.loss()
+        );
+    }
+}
+
+fn update_pings(
+    mut client: Client<Protocol, DefaultChannels>,
+    net_state: Res<State<NetState>>,
+    mut pings: ResMut<Pings>,
+    time: Res<Time>,
+) {
+    if !vec![NetState::Online].contains(net_state.current()) {
+        pings.pending.clear();
+        return;
+    }
+
+    let now = time.seconds_since_startup();
+    let lost = pings.pending.len();
+    pings.pending.retain(|_, sent| now - *sent < PING_DUR_MAX);
+    for _ in pings.pending.len()..lost {
+        pings.push(false);
+    }
+
+    if pings.timer.tick(time.delta()).just_finished() {
+        let id = pings.id;
+        pings.id = id.wrapping_add(1);
+        pings.pending.insert(id, now);
+        client.send_message(DefaultChannels::UnorderedUnreliable, &PingCmd::new(id));
+    }
+}
@@ -1,63 +1,95 @@
-use agentifa_555nake_protocol::protocol::{Auth, Protocol};
+use agentifa_555nake_protocol::protocol::{Auth, KickReason, Protocol, RejectReason, ResumeCmd};
 use bevy::{
     asset::{AssetServer, HandleUntyped},
     ecs::world::{Mut, World},
     input::{
+        gamepad::{GamepadAxis, GamepadButton, Gamepads},
         keyboard::KeyboardInput,
         mouse::{MouseButtonInput, MouseMotion},
+        touch::{TouchInput, Touches},
+        Axis, Input,
     },
     prelude::{
         App, Assets, ClearColor, Color, EventReader, Handle, Image,
-        ParallelSystemDescriptorCoercion, ResMut, State, SystemLabel, Vec2,
+        ParallelSystemDescriptorCoercion, Res, ResMut, State, SystemLabel, Timer, Vec2,
     },
     render::texture::ImageSettings,
     sprite::TextureAtlas,
     text::Font,
-    window::{WindowDescriptor, WindowMode, WindowResizeConstraints, Windows},
+    time::Time,
+    window::{Window, WindowDescriptor, WindowMode, WindowResizeConstraints, Windows},
     DefaultPlugins,
 };
 use bevy_asset_loader::prelude::{AssetCollection, LoadingState, LoadingStateAppExt};
-use bevy_kira_audio::{AudioPlugin, AudioSource};
+use bevy_kira_audio::{AudioApp, AudioPlugin, AudioSource};
+use bindings::Bindings;
+use chat::ChatPlugin;
+use clock::ClockPlugin;
 use game::GamePlugin;
 use gameover::GameOverPlugin;
+use gamepad::Pad;
+use hud::HudPlugin;
 use load::LoadPlugin;
 use menu::MenuPlugin;
 use naia_bevy_client::{
+    events::MessageEvent,
     shared::{DefaultChannels, SharedConfig},
     Client, ClientConfig, Plugin as ClientPlugin, Stage,
 };
-use obfstr::obfstr;
+use profile::ProfilePlugin;
+use rand::random;
 use register::RegisterPlugin;
+use room::RoomPlugin;
+use serde::{Deserialize, Serialize};
+use server::ServerPlugin;
+use settings::{Options, SettingsPlugin};
+use toast::ToastPlugin;
 use vkeyboard::VKeyboardPlugin;
 use wasm_bindgen::prelude::wasm_bindgen;
 
+mod bindings;
+mod chat;
+mod clock;
 mod game;
 mod gameover;
+mod gamepad;
+mod hud;
 mod load;
 mod menu;
+mod profile;
 mod register;
+mod room;
+mod server;
+mod settings;
+mod storage;
+mod toast;
 mod vkeyboard;
 
-#[cfg(debug_assertions)]
-const SRV_ADDR: &str = "127.0.0.1";
-#[cfg(not(debug_assertions))]
-const SRV_ADDR: &str = env!("SRV_ADDR");
-
-#[cfg(not(debug_assertions))]
-const SRV_KEY: &str = env!("SRV_KEY");
-#[cfg(debug_assertions)]
-const SRV_KEY: &str = "SRV_KEY";
+// Used if no server is configured at runtime
+const SRV_ADDR: &str = match option_env!("SRV_ADDR") {
+    Some(addr) => addr,
+    None => "127.0.0.1",
+};
 
-#[cfg(debug_assertions)]
-const SRV_PORT: &str = "55500";
-#[cfg(not(debug_assertions))]
-const SRV_PORT: &str = env!("SRV_PORT");
+const SRV_PORT: &str = match option_env!("SRV_PORT") {
+    Some(port) => port,
+    None => "55500",
+};
 
-#[cfg(debug_assertions)]
-const SRV_PROT: &str = "http";
-#[cfg(not(debug_assertions))]
-const SRV_PROT: &str = env!("SRV_PROT");
+const SRV_PROT: &str = match option_env!("SRV_PROT") {
+    Some(prot) => prot,
+    None => "http",
+};
 
+const AUTH_KEY: &str = "auth";
+const CONNECT_DUR_MAX: f32 = 10.0; // Seconds
+const NAME_KEY: &str = "name";
+const RECONNECT_DUR: f32 = 1.0; // Seconds
+const RECONNECT_DUR_MAX: f32 = 30.0; // Seconds
+#[cfg(target_arch = "wasm32")]
+const SERVER_FILE: &str = "server.json";
+const SERVER_KEY: &str = "server";
+const TOKEN_KEY: &str = "token";
 const WND_CLR: Color = Color::BLACK;
 const WND_TTL: &str = "AGENTIFA 555NAKÉ!";
 const WND_SZE_MIN_X: f32 = 200.0;
@@ -72,7 +104,10 @@ enum AppState {
     Gameover,
     Load,
     Menu,
+    Profile,
     Register,
+    Server,
+    Settings,
 }
 
 #[derive(AssetCollection)]
@@ -123,21 +158,93 @@ struct ImageAssets {
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq, SystemLabel)]
 enum InputState {
+    Gamepad,
     Keyboard,
     Mouse,
+    Touch,
+}
+
+#[derive(Default)]
+struct Connection {
+    attempts: i32,
+    reason: Option<KickReason>,
+    retry: Option<Timer>,
+    session: Option<String>,
+    status: ConnectionStatus,
+    timeout: Timer,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum ConnectionStatus {
+    #[default]
+    Connecting,
+    Disconnected,
+    Rejected,
+    TimedOut,
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 enum NetState {
     Offline,
     Online,
+    Rejected,
 }
 
 #[derive(Default)]
 struct Player {
     name: String,
+    reject: Option<RejectReason>,
+    room: String,
+    token: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct Server {
+    addr: String,
+    port: String,
+    prot: String,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Server {
+            addr: SRV_ADDR.to_string(),
+            port: SRV_PORT.to_string(),
+            prot: SRV_PROT.to_string(),
+        }
+    }
+}
+
+impl Server {
+    /// Parses an URL of the form `[<prot>://]<addr>:<port>`.
+    fn parse(url: &str) -> Option<Self> {
+        let (prot, rest) = url.trim().split_once("://").unwrap_or((SRV_PROT, url.trim()));
+        let (addr, port) = rest.trim_end_matches('/').rsplit_once(':')?;
+        if addr.is_empty() || prot.is_empty() || port.parse::<u16>().is_err() {
+            return None;
+        }
+
+        Some(Server {
+            addr: addr.to_string(),
+            port: port.to_string(),
+            prot: prot.to_string(),
+        })
+    }
+
+    fn save(&self) {
+        if let Ok(json) = serde_json::to_string(self) {
+            storage::save(SERVER_KEY, &json);
+        }
+    }
+
+    fn url(&self) -> String {
+        format!("{}://{}:{}", self.prot, self.addr, self.port)
+    }
 }
 
+/// Audio channel of the sound effects, the music plays on the main channel
+struct Sfx;
+
 #[derive(AssetCollection)]
 struct SpriteSheetAssets {
     #[asset(texture_atlas(tile_size_x = 32.0, tile_size_y = 32.0, columns = 6, rows = 1))]
@@ -171,15 +278,76 @@ struct SpriteSheetAssets {
     pumpkin: Handle<TextureAtlas>,
 }
 
-fn connect(mut net_state: ResMut<State<NetState>>) {
+fn connect(
+    app_state: Res<State<AppState>>,
+    mut client: Client<Protocol, DefaultChannels>,
+    mut connection: ResMut<Connection>,
+    mut net_state: ResMut<State<NetState>>,
+) {
+    connection.attempts = 0;
+    connection.retry = None;
     if vec![NetState::Offline].contains(net_state.current()) {
         net_state.set(NetState::Online).unwrap();
     }
+
+    if let Some(session) = connection.session.clone() {
+        if vec![AppState::Game].contains(app_state.current()) {
+            client.send_message(DefaultChannels::UnorderedReliable, &ResumeCmd::new(session));
+        }
+    }
 }
 
-fn disconnect(mut net_state: ResMut<State<NetState>>) {
+fn disconnect(
+    mut app_state: ResMut<State<AppState>>,
+    mut connection: ResMut<Connection>,
+    mut net_state: ResMut<State<NetState>>,
+) {
     if vec![NetState::Online].contains(net_state.current()) {
+        connection.retry = Some(Timer::from_seconds(RECONNECT_DUR, false));
+        connection.status = ConnectionStatus::Disconnected;
         net_state.set(NetState::Offline).unwrap();
+        let states = vec![AppState::Connect, AppState::Game, AppState::Load, AppState::Server];
+        if !states.contains(app_state.current()) {
+            app_state.set(AppState::Connect).unwrap();
+        }
+    }
+}
+
+fn kick_message(
+    mut app_state: ResMut<State<AppState>>,
+    mut connection: ResMut<Connection>,
+    mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
+    mut net_state: ResMut<State<NetState>>,
+) {
+    for event in event_reader.iter() {
+        if let MessageEvent(_, Protocol::KickMsg(msg)) = event {
+            connection.reason = Some(*msg.reason);
+            connection.retry = None;
+            connection.status = ConnectionStatus::Rejected;
+            if !vec![NetState::Rejected].contains(net_state.current()) {
+                net_state.set(NetState::Rejected).unwrap();
+            }
+
+            if !vec![AppState::Connect, AppState::Load].contains(app_state.current()) {
+                app_state.set(AppState::Connect).unwrap();
+            }
+        }
+    }
+}
+
+fn input_gamepad(
+    axes: Res<Axis<GamepadAxis>>,
+    buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    mut pad: ResMut<Pad>,
+    mut state: ResMut<InputState>,
+    mut windows: ResMut<Windows>,
+) {
+    pad.update(&axes, &buttons, &gamepads);
+    if pad.any_just_pressed() && !vec![InputState::Gamepad].contains(&state) {
+        let wnd = windows.get_primary_mut().unwrap();
+        wnd.set_cursor_visibility(false);
+        *state = InputState::Gamepad;
     }
 }
 
@@ -199,9 +367,13 @@ fn input_mouse(
     mut input_button: EventReader<MouseButtonInput>,
     mut input_motion: EventReader<MouseMotion>,
     mut state: ResMut<InputState>,
+    touches: Res<Touches>,
     mut windows: ResMut<Windows>,
 ) {
+    // Browsers emulate mouse events for touches
+    let touched = touches.iter().count() > 0 || touches.iter_just_released().count() > 0;
     if (input_button.iter().count() > 0 || input_motion.iter().count() > 0)
+        && !touched
         && !vec![InputState::Mouse].contains(&state)
     {
         let wnd = windows.get_primary_mut().unwrap();
@@ -210,18 +382,163 @@ fn input_mouse(
     }
 }
 
-fn setup(mut client: Client<Protocol, DefaultChannels>) {
-    client.auth(Auth::new(obfstr!(SRV_KEY)));
-    client.connect(&format!("{}://{}:{}", SRV_PROT, SRV_ADDR, SRV_PORT));
+fn input_touch(
+    mut input: EventReader<TouchInput>,
+    mut state: ResMut<InputState>,
+    mut windows: ResMut<Windows>,
+) {
+    if input.iter().count() > 0 && !vec![InputState::Touch].contains(&state) {
+        let wnd = windows.get_primary_mut().unwrap();
+        wnd.set_cursor_visibility(false);
+        *state = InputState::Touch;
+    }
+}
+
+/// Reads the auth token handed out by the server admin. On wasm32 it can be
+/// passed as `?auth=<token>` and is remembered in the local storage.
+fn load_auth() -> String {
+    #[cfg(target_arch = "wasm32")]
+    if let Some(auth) = storage::query(AUTH_KEY) {
+        storage::save(AUTH_KEY, &auth);
+        return auth;
+    }
+
+    storage::load(AUTH_KEY)
+        .map(|a| a.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Reads the server to connect to. On wasm32 it can be passed as
+/// `?server=<prot>://<addr>:<port>` or configured in a `server.json` next to
+/// the `index.html`. The last server in use is remembered.
+fn load_server() -> Server {
+    #[cfg(target_arch = "wasm32")]
+    if let Some(server) = storage::query(SERVER_KEY).and_then(|url| Server::parse(&url)) {
+        server.save();
+        return server;
+    }
+
+    if let Some(server) = storage::load(SERVER_KEY).and_then(|j| serde_json::from_str(&j).ok()) {
+        return server;
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    if let Some(server) =
+        storage::fetch(SERVER_FILE).and_then(|j| serde_json::from_str(&j).ok())
+    {
+        return server;
+    }
+
+    Server::default()
+}
+
+fn load_token() -> String {
+    if let Some(token) = storage::load(TOKEN_KEY).filter(|t| !t.is_empty()) {
+        return token;
+    }
+
+    let token = format!("{:032x}", random::<u128>());
+    storage::save(TOKEN_KEY, &token);
+    token
+}
+
+/// Connects again after the connection dropped, doubling the delay between
+/// attempts up to `RECONNECT_DUR_MAX`.
+fn reconnect(
+    mut client: Client<Protocol, DefaultChannels>,
+    mut connection: ResMut<Connection>,
+    server: Res<Server>,
+    time: Res<Time>,
+) {
+    match connection.retry.as_mut() {
+        Some(timer) if timer.tick(time.delta()).finished() => (),
+        _ => return,
+    }
+
+    connection.attempts += 1;
+    let dur = RECONNECT_DUR * 2f32.powi(connection.attempts);
+    connection.retry = Some(Timer::from_seconds(dur.min(RECONNECT_DUR_MAX), false));
+    if !client.is_connected() {
+        start_connection(&mut client, &mut connection, &server);
+    }
+}
+
+/// Starts over after the connection was rejected, timed out or dropped.
+fn retry_connection(
+    client: &mut Client<Protocol, DefaultChannels>,
+    connection: &mut Connection,
+    net_state: &mut State<NetState>,
+    server: &Server,
+) {
+    connection.attempts = 0;
+    connection.reason = None;
+    connection.retry = None;
+    connection.status = ConnectionStatus::Connecting;
+    if vec![NetState::Rejected].contains(net_state.current()) {
+        net_state.set(NetState::Offline).unwrap();
+    }
+
+    if !client.is_connected() {
+        start_connection(client, connection, server);
+    }
+}
+
+fn setup(
+    mut client: Client<Protocol, DefaultChannels>,
+    mut connection: ResMut<Connection>,
+    server: Res<Server>,
+) {
+    start_connection(&mut client, &mut connection, &server);
+}
+
+fn start_connection(
+    client: &mut Client<Protocol, DefaultChannels>,
+    connection: &mut Connection,
+    server: &Server,
+) {
+    connection.timeout = Timer::from_seconds(CONNECT_DUR_MAX, false);
+    client.auth(Auth::new(&load_auth()));
+    client.connect(&server.url());
+}
+
+/// Position of a touch relative to the window centre like the cursor in the
+/// input systems. Touches count from the top left corner.
+fn touch_position(wnd: &Window, position: Vec2) -> Vec2 {
+    Vec2::new(position.x, wnd.height() - position.y) - 0.5 * Vec2::new(wnd.width(), wnd.height())
+}
+
+fn update_timeout(
+    mut connection: ResMut<Connection>,
+    net_state: Res<State<NetState>>,
+    time: Res<Time>,
+) {
+    if !vec![NetState::Offline].contains(net_state.current())
+        || connection.status != ConnectionStatus::Connecting
+    {
+        return;
+    }
+
+    if connection.timeout.tick(time.delta()).just_finished() {
+        connection.status = ConnectionStatus::TimedOut;
+    }
 }
 
 #[wasm_bindgen]
 pub fn start() {
     App::new()
+        .insert_resource(Bindings::load())
         .insert_resource(ClearColor(WND_CLR))
+        .insert_resource(Connection::default())
         .insert_resource(ImageSettings::default_nearest())
         .insert_resource(InputState::Mouse)
-        .insert_resource(Player::default())
+        .insert_resource(Options::load())
+        .insert_resource(Pad::default())
+        .insert_resource(Player {
+            name: storage::load(NAME_KEY).unwrap_or_default(),
+            token: load_token(),
+            ..Default::default()
+        })
+        .insert_resource(load_server())
         .insert_resource(WindowDescriptor {
             height: WND_SZE_Y,
             mode: WindowMode::Windowed,
@@ -244,21 +561,35 @@ pub fn start() {
         )
         .add_plugins(DefaultPlugins)
         .add_plugin(AudioPlugin)
+        .add_audio_channel::<Sfx>()
         .add_plugin(ClientPlugin::<Protocol, DefaultChannels>::new(
             ClientConfig::default(),
             SharedConfig::default(),
         ))
+        .add_plugin(ChatPlugin)
+        .add_plugin(ClockPlugin)
         .add_plugin(GamePlugin)
         .add_plugin(GameOverPlugin)
+        .add_plugin(HudPlugin)
         .add_plugin(LoadPlugin)
         .add_plugin(MenuPlugin)
+        .add_plugin(ProfilePlugin)
         .add_plugin(RegisterPlugin)
+        .add_plugin(RoomPlugin)
+        .add_plugin(ServerPlugin)
+        .add_plugin(SettingsPlugin)
+        .add_plugin(ToastPlugin)
         .add_plugin(VKeyboardPlugin)
         .add_startup_system(setup)
         .add_state(AppState::Load)
         .add_state(NetState::Offline)
+        .add_system(input_gamepad.label(InputState::Gamepad))
         .add_system(input_keyboard.label(InputState::Keyboard))
         .add_system(input_mouse.label(InputState::Mouse))
+        .add_system(input_touch.label(InputState::Touch))
+        .add_system(kick_message)
+        .add_system(reconnect)
+        .add_system(update_timeout)
         .add_system_to_stage(Stage::Connection, connect)
         .add_system_to_stage(Stage::Disconnection, disconnect)
         .run();
@@ -1,12 +1,15 @@
 use std::f32::consts::PI;
 
+use agentifa_555nake_protocol::protocol::{KickReason, Protocol};
 use bevy::{
     ecs::schedule::ShouldRun,
+    input::{touch::Touches, Input},
     math::{Quat, Vec2},
     prelude::{
         App, AssetServer, BuildChildren, Camera2dBundle, Color, Commands, Component,
-        DespawnRecursiveExt, Entity, Handle, Image, In, IntoChainSystem, NodeBundle, Plugin, Query,
-        Res, ResMut, State, SystemSet, TextBundle, Transform, UiCameraConfig, With,
+        DespawnRecursiveExt, Entity, Handle, Image, In, IntoChainSystem, KeyCode, MouseButton,
+        NodeBundle, ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut, State,
+        SystemSet, TextBundle, Transform, UiCameraConfig, With, Without,
     },
     sprite::SpriteBundle,
     text::{Font, Text, TextStyle},
@@ -15,17 +18,40 @@ use bevy::{
     window::Windows,
 };
 
-use crate::{AppState, FontAssets, ImageAssets, NetState};
+use naia_bevy_client::{shared::DefaultChannels, Client};
+
+use crate::{
+    bindings::{Action, Bindings},
+    retry_connection, AppState, Connection, ConnectionStatus, FontAssets, ImageAssets, InputState,
+    NetState, Server,
+};
 
 const CONTXT: &str = "Verbinde";
+const DISCONNECT_TXT: &str = "Getrennt";
+const DISCONNECT_TXT_DETAIL: &str = "The connection to the server was lost";
+const HINT_SZE: f32 = 20.0; // Font size
+const HINT_TXT: &str = "Enter / Click: retry";
+const HINT_TXT_SERVER: &str = "Tab / Right click: change server";
 const LOADCLR: Color = Color::CYAN;
 const LOADFNT: &str = "font/RobotoMono-Bold.ttf";
 const LOADIMG: &str = "image/load.png";
 const LOADSZE: f32 = 64.0; // Font size
 const LOADTXT: &str = "Lade";
+const MAINTENANCE_TXT: &str = "Wartung";
+const MAINTENANCE_TXT_DETAIL: &str = "The server is shutting down for maintenance";
 const MARQUEE: [&str; 3] = [".  ", " . ", "  ."];
 const MARQUEE_SPEED: f64 = 1.0; // Seconds per step
+const REJECT_SZE: f32 = 24.0; // Font size
+const REJECT_TXT: &str = "Abgelehnt";
+const REJECT_TXT_BANNED: &str = "You are banned from this server";
+const REJECT_TXT_EXPIRED: &str = "Your access token has expired";
+const REJECT_TXT_INVALID: &str = "Your access token is not valid";
+const REJECT_TXT_KICKED: &str = "You were kicked by an admin";
+const REJECT_TXT_MALFORMED: &str = "No valid access token found";
+const REJECT_TXT_VIOLATIONS: &str = "Your client sent too many invalid messages";
 const ROTATION_SPEED: f32 = PI * 0.2; // Angle per Second
+const TIMEOUT_TXT: &str = "Zeitüberschreitung";
+const TIMEOUT_TXT_DETAIL: &str = "The server did not respond";
 
 pub struct LoadPlugin;
 impl Plugin for LoadPlugin {
@@ -33,8 +59,14 @@ impl Plugin for LoadPlugin {
         let with_load_systems =
             |s: SystemSet| -> SystemSet { s.with_system(rotate).with_system(transform) };
 
-        let with_connect_systems =
-            |s: SystemSet| -> SystemSet { with_load_systems(s).with_system(connect) };
+        let with_connect_systems = |s: SystemSet| -> SystemSet {
+            with_load_systems(s)
+                .with_system(connect)
+                .with_system(input_keyboard.after(InputState::Keyboard))
+                .with_system(input_mouse.after(InputState::Mouse))
+                .with_system(input_touch.after(InputState::Touch))
+                .with_system(update_status)
+        };
 
         app.add_system_set(SystemSet::on_enter(AppState::Connect).with_system(setup_connect))
             .add_system_set(SystemSet::on_exit(AppState::Connect).with_system(cleanup))
@@ -60,6 +92,9 @@ impl Plugin for LoadPlugin {
     }
 }
 
+#[derive(Component)]
+struct Hint;
+
 #[derive(Component)]
 struct LoadComponent;
 
@@ -73,6 +108,9 @@ struct Marquee {
     step: usize,
 }
 
+#[derive(Component)]
+struct Status;
+
 fn cleanup(mut commands: Commands, query: Query<Entity, With<LoadComponent>>) {
     for entity in query.iter() {
         commands.entity(entity).despawn_recursive();
@@ -85,6 +123,79 @@ fn connect(mut app_state: ResMut<State<AppState>>, net_state: Res<State<NetState
     }
 }
 
+fn input_keyboard(
+    mut app_state: ResMut<State<AppState>>,
+    bindings: Res<Bindings>,
+    mut client: Client<Protocol, DefaultChannels>,
+    mut connection: ResMut<Connection>,
+    mut input: ResMut<Input<KeyCode>>,
+    input_state: Res<InputState>,
+    mut net_state: ResMut<State<NetState>>,
+    server: Res<Server>,
+) {
+    if !vec![InputState::Keyboard].contains(&input_state) {
+        return;
+    }
+
+    if bindings.take(&mut input, Action::Switch) {
+        app_state.set(AppState::Server).unwrap();
+        return;
+    }
+
+    if connection.status != ConnectionStatus::Connecting
+        && bindings.take(&mut input, Action::Confirm)
+    {
+        retry_connection(&mut client, &mut connection, &mut net_state, &server);
+    }
+}
+
+fn input_mouse(
+    mut app_state: ResMut<State<AppState>>,
+    mut client: Client<Protocol, DefaultChannels>,
+    mut connection: ResMut<Connection>,
+    input: Res<Input<MouseButton>>,
+    input_state: Res<InputState>,
+    mut net_state: ResMut<State<NetState>>,
+    server: Res<Server>,
+) {
+    if !vec![InputState::Mouse].contains(&input_state) {
+        return;
+    }
+
+    if input.just_pressed(MouseButton::Right) {
+        app_state.set(AppState::Server).unwrap();
+        return;
+    }
+
+    if input.just_pressed(MouseButton::Left) && connection.status != ConnectionStatus::Connecting {
+        retry_connection(&mut client, &mut connection, &mut net_state, &server);
+    }
+}
+
+/// A tap retries, a tap with two fingers opens the server address
+fn input_touch(
+    mut app_state: ResMut<State<AppState>>,
+    mut client: Client<Protocol, DefaultChannels>,
+    mut connection: ResMut<Connection>,
+    input_state: Res<InputState>,
+    mut net_state: ResMut<State<NetState>>,
+    server: Res<Server>,
+    touches: Res<Touches>,
+) {
+    if !vec![InputState::Touch].contains(&input_state) || touches.iter_just_pressed().count() == 0 {
+        return;
+    }
+
+    if touches.iter().count() > 1 {
+        app_state.set(AppState::Server).unwrap();
+        return;
+    }
+
+    if connection.status != ConnectionStatus::Connecting {
+        retry_connection(&mut client, &mut connection, &mut net_state, &server);
+    }
+}
+
 fn rotate(mut query: Query<&mut Rotation>, time: Res<Time>) {
     for mut rotation in query.iter_mut() {
         rotation.angle += time.delta().as_secs_f32() * ROTATION_SPEED;
@@ -119,7 +230,7 @@ fn setup(cmd: &mut Commands, fnt: Handle<Font>, img: Handle<Image>, txt: &str) {
         ..Default::default()
     })
     .with_children(|parent| {
-        parent.spawn_bundle(text(fnt.clone(), txt));
+        parent.spawn_bundle(text(fnt.clone(), txt)).insert(Status);
     })
     .with_children(|parent| {
         let marquee = Marquee::default();
@@ -127,6 +238,11 @@ fn setup(cmd: &mut Commands, fnt: Handle<Font>, img: Handle<Image>, txt: &str) {
             .spawn_bundle(text(fnt.clone(), MARQUEE[marquee.step]))
             .insert(marquee);
     })
+    .with_children(|parent| {
+        let mut hint = text(fnt.clone(), "");
+        hint.text.sections[0].style.font_size = HINT_SZE;
+        parent.spawn_bundle(hint).insert(Hint);
+    })
     .insert(LoadComponent);
 }
 
@@ -171,7 +287,11 @@ fn transform(mut query: Query<(&Rotation, &mut Transform)>, windows: Res<Windows
     }
 }
 
-fn update_marquee(mut query: Query<(&mut Marquee, &mut Text)>) {
+fn update_marquee(connection: Res<Connection>, mut query: Query<(&mut Marquee, &mut Text)>) {
+    if connection.status != ConnectionStatus::Connecting {
+        return;
+    }
+
     for (mut marquee, mut text) in query.iter_mut() {
         marquee.step += 1;
         if marquee.step >= MARQUEE.len() {
@@ -181,3 +301,51 @@ fn update_marquee(mut query: Query<(&mut Marquee, &mut Text)>) {
         text.sections[0].value = format!("{}", MARQUEE[marquee.step]);
     }
 }
+
+fn update_status(
+    connection: Res<Connection>,
+    mut hint: Query<&mut Text, (With<Hint>, Without<Marquee>, Without<Status>)>,
+    mut marquee: Query<(&Marquee, &mut Text), (Without<Hint>, Without<Status>)>,
+    mut status: Query<&mut Text, (With<Status>, Without<Hint>, Without<Marquee>)>,
+) {
+    let (title, detail) = match (connection.status, connection.reason) {
+        (ConnectionStatus::Connecting, _) => (CONTXT, None),
+        (ConnectionStatus::Disconnected, _) => (DISCONNECT_TXT, Some(DISCONNECT_TXT_DETAIL)),
+        (ConnectionStatus::Rejected, Some(KickReason::AuthExpired)) => {
+            (REJECT_TXT, Some(REJECT_TXT_EXPIRED))
+        }
+        (ConnectionStatus::Rejected, Some(KickReason::AuthInvalid)) => {
+            (REJECT_TXT, Some(REJECT_TXT_INVALID))
+        }
+        (ConnectionStatus::Rejected, Some(KickReason::Banned)) => {
+            (REJECT_TXT, Some(REJECT_TXT_BANNED))
+        }
+        (ConnectionStatus::Rejected, Some(KickReason::Kicked)) => {
+            (REJECT_TXT, Some(REJECT_TXT_KICKED))
+        }
+        (ConnectionStatus::Rejected, Some(KickReason::Shutdown)) => {
+            (MAINTENANCE_TXT, Some(MAINTENANCE_TXT_DETAIL))
+        }
+        (ConnectionStatus::Rejected, Some(KickReason::Violations)) => {
+            (REJECT_TXT, Some(REJECT_TXT_VIOLATIONS))
+        }
+        (ConnectionStatus::Rejected, _) => (REJECT_TXT, Some(REJECT_TXT_MALFORMED)),
+        (ConnectionStatus::TimedOut, _) => (TIMEOUT_TXT, Some(TIMEOUT_TXT_DETAIL)),
+    };
+
+    for mut text in status.iter_mut() {
+        text.sections[0].value = title.to_string();
+    }
+
+    for (marquee, mut text) in marquee.iter_mut() {
+        text.sections[0].style.font_size = detail.map_or(LOADSZE, |_| REJECT_SZE);
+        text.sections[0].value = detail.unwrap_or(MARQUEE[marquee.step]).to_string();
+    }
+
+    for mut text in hint.iter_mut() {
+        text.sections[0].value = match detail {
+            Some(_) => format!("{}\n{}", HINT_TXT, HINT_TXT_SERVER),
+            None => HINT_TXT_SERVER.to_string(),
+        };
+    }
+}
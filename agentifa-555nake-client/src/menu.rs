@@ -1,5 +1,6 @@
 use std::fmt;
 
+use agentifa_555nake_protocol::protocol::Direction;
 use bevy::{
     core_pipeline::clear_color::ClearColor,
     input::Input,
@@ -15,12 +16,18 @@ use bevy::{
     ui::{
         AlignItems, FlexDirection, Interaction, JustifyContent, Size, Style, UiColor, UiRect, Val,
     },
-    window::{WindowMode, Windows},
+    window::Windows,
 };
-use bevy_kira_audio::{Audio, AudioControl};
+use bevy_kira_audio::{Audio, AudioChannel, AudioControl};
 use rand::random;
 
-use crate::{AppState, AudioAssets, FontAssets, ImageAssets, InputState};
+use crate::{
+    bindings::{Action, Bindings},
+    chat::Chat,
+    gamepad::{Pad, PadButton},
+    settings::Options,
+    AppState, AudioAssets, FontAssets, ImageAssets, InputState, Sfx,
+};
 
 #[cfg(not(target_arch = "wasm32"))]
 use bevy::app::AppExit;
@@ -36,6 +43,8 @@ const BTN_TXT_CLR_HOV: Color = Color::BLACK;
 const BTN_TXT_HSC: &str = "High555coré";
 const BTN_TXT_MGN: f32 = 10.0; // PX
 const BTN_TXT_FSCR: &str = "Toggle Fullscreen";
+const BTN_TXT_PROFILE: &str = "Agent Profile";
+const BTN_TXT_SETTINGS: &str = "Settings";
 const BTN_TXT_START: &str = "555tart Gamé";
 const BTN_TXT_SZE: f32 = 30.0; // Font Size
 const BTN_SZE: f32 = 50.0; // Px
@@ -58,8 +67,10 @@ impl Plugin for MenuPlugin {
             .add_system_set(SystemSet::on_exit(AppState::Menu).with_system(cleanup))
             .add_system_set(
                 SystemSet::on_update(AppState::Menu)
+                    .with_system(input_gamepad.after(InputState::Gamepad))
                     .with_system(input_keyboard.after(InputState::Keyboard))
                     .with_system(input_mouse.after(InputState::Mouse))
+                    .with_system(input_touch.after(InputState::Touch))
                     .with_system(navigation)
                     .with_system(update_bg),
             );
@@ -74,6 +85,8 @@ enum MenuButton {
     FScr,
     Start,
     HighScore,
+    Profile,
+    Settings,
     #[cfg(not(target_arch = "wasm32"))]
     Quit,
 }
@@ -92,6 +105,8 @@ impl fmt::Display for MenuButton {
             MenuButton::FScr => BTN_TXT_FSCR,
             MenuButton::Start => BTN_TXT_START,
             MenuButton::HighScore => BTN_TXT_HSC,
+            MenuButton::Profile => BTN_TXT_PROFILE,
+            MenuButton::Settings => BTN_TXT_SETTINGS,
             #[cfg(not(target_arch = "wasm32"))]
             MenuButton::Quit => BTN_TXT_QUIT,
         };
@@ -104,10 +119,12 @@ impl MenuButton {
         match *self {
             MenuButton::FScr => MenuButton::Start,
             MenuButton::Start => MenuButton::HighScore,
+            MenuButton::HighScore => MenuButton::Profile,
+            MenuButton::Profile => MenuButton::Settings,
             #[cfg(not(target_arch = "wasm32"))]
-            MenuButton::HighScore => MenuButton::Quit,
+            MenuButton::Settings => MenuButton::Quit,
             #[cfg(target_arch = "wasm32")]
-            MenuButton::HighScore => MenuButton::FScr,
+            MenuButton::Settings => MenuButton::FScr,
             #[cfg(not(target_arch = "wasm32"))]
             MenuButton::Quit => MenuButton::FScr,
         }
@@ -118,11 +135,13 @@ impl MenuButton {
             #[cfg(not(target_arch = "wasm32"))]
             MenuButton::FScr => MenuButton::Quit,
             #[cfg(target_arch = "wasm32")]
-            MenuButton::FScr => MenuButton::HighScore,
+            MenuButton::FScr => MenuButton::Settings,
             MenuButton::Start => MenuButton::FScr,
             MenuButton::HighScore => MenuButton::Start,
+            MenuButton::Profile => MenuButton::HighScore,
+            MenuButton::Settings => MenuButton::Profile,
             #[cfg(not(target_arch = "wasm32"))]
-            MenuButton::Quit => MenuButton::HighScore,
+            MenuButton::Quit => MenuButton::Settings,
         }
     }
 }
@@ -195,33 +214,64 @@ fn content() -> NodeBundle {
     }
 }
 
+fn input_gamepad(
+    chat: Res<Chat>,
+    input_state: Res<InputState>,
+    menu_state: Query<&MenuState>,
+    pad: Res<Pad>,
+    mut writer: EventWriter<MenuEvent>,
+) {
+    if chat.open || !vec![InputState::Gamepad].contains(&input_state) {
+        return;
+    }
+
+    let mnu = menu_state.iter().next().unwrap();
+    if pad.just_pressed(PadButton::Down) {
+        writer.send(MenuEvent {
+            act: Interaction::Hovered,
+            btn: mnu.button.next(),
+        });
+    }
+    if pad.just_pressed(PadButton::Up) {
+        writer.send(MenuEvent {
+            act: Interaction::Hovered,
+            btn: mnu.button.prev(),
+        });
+    }
+    if pad.just_pressed(PadButton::South) || pad.just_pressed(PadButton::Start) {
+        writer.send(MenuEvent {
+            act: Interaction::Clicked,
+            btn: mnu.button,
+        });
+    }
+}
+
 fn input_keyboard(
+    bindings: Res<Bindings>,
+    chat: Res<Chat>,
     mut input: ResMut<Input<KeyCode>>,
     input_state: Res<InputState>,
     menu_state: Query<&MenuState>,
     mut writer: EventWriter<MenuEvent>,
 ) {
-    if !vec![InputState::Keyboard].contains(&input_state) {
+    if chat.open || !vec![InputState::Keyboard].contains(&input_state) {
         return;
     }
 
     let mnu = menu_state.iter().next().unwrap();
-    if input.pressed(KeyCode::Down) {
-        input.release(KeyCode::Down);
+    if bindings.take(&mut input, Action::Steer(Direction::Down)) {
         writer.send(MenuEvent {
             act: Interaction::Hovered,
             btn: mnu.button.next(),
         });
     }
-    if input.pressed(KeyCode::Up) {
-        input.release(KeyCode::Up);
+    if bindings.take(&mut input, Action::Steer(Direction::Up)) {
         writer.send(MenuEvent {
             act: Interaction::Hovered,
             btn: mnu.button.prev(),
         });
     }
-    if input.just_pressed(KeyCode::Return) {
-        input.release(KeyCode::Return);
+    if bindings.take(&mut input, Action::Confirm) {
         writer.send(MenuEvent {
             act: Interaction::Clicked,
             btn: mnu.button,
@@ -231,12 +281,13 @@ fn input_keyboard(
 
 fn input_mouse(
     buttons: Query<(&MenuButton, &GlobalTransform)>,
+    chat: Res<Chat>,
     input: Res<Input<MouseButton>>,
     input_state: Res<InputState>,
     windows: ResMut<Windows>,
     mut writer: EventWriter<MenuEvent>,
 ) {
-    if !vec![InputState::Mouse].contains(&input_state) {
+    if chat.open || !vec![InputState::Mouse].contains(&input_state) {
         return;
     }
 
@@ -272,6 +323,36 @@ fn input_mouse(
     }
 }
 
+/// Taps go through the UI interaction, which follows the touches
+fn input_touch(
+    buttons: Query<(&MenuButton, &Interaction), Changed<Interaction>>,
+    chat: Res<Chat>,
+    input_state: Res<InputState>,
+    mut writer: EventWriter<MenuEvent>,
+) {
+    if chat.open || !vec![InputState::Touch].contains(&input_state) {
+        return;
+    }
+
+    for (btn, interaction) in buttons.iter() {
+        if *interaction == Interaction::None {
+            continue;
+        }
+
+        writer.send(MenuEvent {
+            act: Interaction::Hovered,
+            btn: *btn,
+        });
+
+        if *interaction == Interaction::Clicked {
+            writer.send(MenuEvent {
+                act: Interaction::Clicked,
+                btn: *btn,
+            });
+        }
+    }
+}
+
 fn menu() -> NodeBundle {
     NodeBundle {
         color: MNU_CLR.into(),
@@ -288,29 +369,30 @@ fn menu() -> NodeBundle {
 }
 
 fn navigation(
-    audio: Res<Audio>,
+    audio: Res<AudioChannel<Sfx>>,
     mut clear: ResMut<ClearColor>,
     #[cfg(not(target_arch = "wasm32"))] mut exit: EventWriter<AppExit>,
+    mut options: ResMut<Options>,
     mut query: Query<(&MenuButton, &Children, &mut UiColor)>,
     mut reader: EventReader<MenuEvent>,
     sounds: Res<AudioAssets>,
     mut state_app: ResMut<State<AppState>>,
     mut state_mnu: Query<&mut MenuState>,
     mut text: Query<&mut Text>,
-    mut windows: ResMut<Windows>,
 ) {
     clear.0 = Color::BLACK;
     let mut mnu = state_mnu.iter_mut().next().unwrap();
     for e in reader.iter() {
-        let wnd = windows.get_primary_mut().unwrap();
         match e.act {
             Interaction::Clicked => match e.btn {
-                MenuButton::FScr => wnd.set_mode(match wnd.mode() {
-                    WindowMode::Windowed => WindowMode::BorderlessFullscreen,
-                    _ => WindowMode::Windowed,
-                }),
+                MenuButton::FScr => {
+                    options.fullscreen = !options.fullscreen;
+                    options.save();
+                }
                 MenuButton::Start => state_app.set(AppState::Register).unwrap(),
                 MenuButton::HighScore => state_app.set(AppState::Gameover).unwrap(),
+                MenuButton::Profile => state_app.set(AppState::Profile).unwrap(),
+                MenuButton::Settings => state_app.set(AppState::Settings).unwrap(),
                 #[cfg(not(target_arch = "wasm32"))]
                 MenuButton::Quit => exit.send(AppExit),
             },
@@ -415,6 +497,8 @@ fn setup(
                         spawn_button(parent, MenuButton::FScr);
                         spawn_button(parent, MenuButton::Start);
                         spawn_button(parent, MenuButton::HighScore);
+                        spawn_button(parent, MenuButton::Profile);
+                        spawn_button(parent, MenuButton::Settings);
 
                         #[cfg(not(target_arch = "wasm32"))]
                         spawn_button(parent, MenuButton::Quit);
@@ -0,0 +1,329 @@
+use agentifa_555nake_protocol::protocol::{Protocol, StatsCmd};
+use bevy::{
+    core_pipeline::clear_color::ClearColor,
+    input::{touch::Touches, Input},
+    math::{Vec2, Vec3},
+    prelude::{
+        App, BuildChildren, Camera2dBundle, Color, Commands, Component, DespawnRecursiveExt,
+        Entity, EventReader, KeyCode, MouseButton, NodeBundle, ParallelSystemDescriptorCoercion,
+        Plugin, Query, Res, ResMut, State, SystemSet, TextBundle, Transform, UiCameraConfig, With,
+    },
+    sprite::{SpriteSheetBundle, TextureAtlasSprite},
+    text::{Text, TextSection, TextStyle},
+    ui::{AlignItems, FlexDirection, JustifyContent, Size, Style, UiRect, Val},
+    window::Windows,
+};
+use naia_bevy_client::{events::MessageEvent, shared::DefaultChannels, Client};
+
+use crate::{
+    bindings::{Action, Bindings},
+    gamepad::{Pad, PadButton},
+    touch_position, AppState, FontAssets, InputState, Player, SpriteSheetAssets,
+};
+
+const BTN_COLOR: Color = Color::rgba(1., 1., 1., 0.5);
+const BTN_ESC_IDX: usize = 0;
+const ENTRY_SIZE: f32 = 30.;
+const GRID_SIZE: u8 = 10;
+const LABEL_COLOR: Color = Color::CYAN;
+const NAME_COLOR: Color = Color::PINK;
+const TITLE_COLOR: Color = Color::YELLOW;
+const TITLE_SIZE: f32 = 60.;
+const TITLE_TEXT: &str = "AGENT PROFILE";
+const TXT_AVERAGE: &str = "Average score";
+const TXT_DEATHS_QUIT: &str = "Missions aborted";
+const TXT_DEATHS_SELF: &str = "Bitten own tail";
+const TXT_DEATHS_SNAKE: &str = "Crashed into agents";
+const TXT_FOOD: &str = "Broccoli eaten";
+const TXT_GAMES: &str = "Games played";
+const TXT_LOAD: &str = "Lade...";
+const TXT_NO_NAME: &str = "No Agent ID registered yet";
+const TXT_SURVIVAL: &str = "Longest survival";
+const VALUE_COLOR: Color = Color::YELLOW;
+
+pub struct ProfilePlugin;
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Profile).with_system(setup))
+            .add_system_set(SystemSet::on_exit(AppState::Profile).with_system(cleanup))
+            .add_system_set(
+                SystemSet::on_update(AppState::Profile)
+                    .with_system(input_gamepad.after(InputState::Gamepad))
+                    .with_system(input_keyboard.after(InputState::Keyboard))
+                    .with_system(input_mouse.after(InputState::Mouse))
+                    .with_system(input_touch.after(InputState::Touch))
+                    .with_system(stats_message)
+                    .with_system(update_buttons),
+            );
+    }
+}
+
+#[derive(Component)]
+struct Button;
+
+#[derive(Component)]
+struct ProfileComponent;
+
+#[derive(Component)]
+struct StatsText;
+
+fn cleanup(mut commands: Commands, query: Query<Entity, With<ProfileComponent>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn input_gamepad(
+    mut app_state: ResMut<State<AppState>>,
+    input_state: Res<InputState>,
+    pad: Res<Pad>,
+) {
+    if !vec![InputState::Gamepad].contains(&input_state) {
+        return;
+    }
+
+    if pad.just_pressed(PadButton::East) || pad.just_pressed(PadButton::Start) {
+        app_state.set(AppState::Menu).unwrap();
+    }
+}
+
+fn input_keyboard(
+    mut app_state: ResMut<State<AppState>>,
+    bindings: Res<Bindings>,
+    mut input: ResMut<Input<KeyCode>>,
+    input_state: Res<InputState>,
+) {
+    if !vec![InputState::Keyboard].contains(&input_state) {
+        return;
+    }
+
+    if bindings.take(&mut input, Action::Back) {
+        app_state.set(AppState::Menu).unwrap();
+    }
+}
+
+fn input_mouse(
+    mut app_state: ResMut<State<AppState>>,
+    buttons: Query<&Transform, With<Button>>,
+    input: Res<Input<MouseButton>>,
+    input_state: Res<InputState>,
+    windows: Res<Windows>,
+) {
+    if !vec![InputState::Mouse].contains(&input_state) || !input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let wnd = windows.get_primary().unwrap();
+    if let Some(mut cursor) = wnd.cursor_position() {
+        cursor -= 0.5 * Vec2::new(wnd.width(), wnd.height());
+        let contains = |p: Vec2, a: UiRect<f32>| {
+            p.x > a.left && p.x < a.right && p.y > a.bottom && p.y < a.top
+        };
+
+        let tf = buttons.get_single().unwrap();
+        let offs = 0.5 * tf.scale;
+        let rect = UiRect {
+            bottom: tf.translation.y - offs.y,
+            left: tf.translation.x - offs.x,
+            right: tf.translation.x + offs.x,
+            top: tf.translation.y + offs.y,
+        };
+
+        if contains(cursor, rect) {
+            app_state.set(AppState::Menu).unwrap();
+        }
+    }
+}
+
+fn input_touch(
+    mut app_state: ResMut<State<AppState>>,
+    buttons: Query<&Transform, With<Button>>,
+    input_state: Res<InputState>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+) {
+    if !vec![InputState::Touch].contains(&input_state) {
+        return;
+    }
+
+    let wnd = windows.get_primary().unwrap();
+    let tf = buttons.get_single().unwrap();
+    let offs = 0.5 * tf.scale;
+    for touch in touches.iter_just_released() {
+        let pos = touch_position(wnd, touch.position());
+        if pos.x > tf.translation.x - offs.x
+            && pos.x < tf.translation.x + offs.x
+            && pos.y > tf.translation.y - offs.y
+            && pos.y < tf.translation.y + offs.y
+        {
+            app_state.set(AppState::Menu).unwrap();
+            return;
+        }
+    }
+}
+
+fn section(fonts: &FontAssets, color: Color, value: String) -> TextSection {
+    TextSection {
+        value,
+        style: TextStyle {
+            color,
+            font: fonts.regular.clone(),
+            font_size: ENTRY_SIZE,
+        },
+    }
+}
+
+fn setup(
+    mut clear: ResMut<ClearColor>,
+    mut client: Client<Protocol, DefaultChannels>,
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    player: Res<Player>,
+    sheets: Res<SpriteSheetAssets>,
+) {
+    clear.0 = Color::BLACK;
+    commands
+        .spawn_bundle(Camera2dBundle::default())
+        .insert(ProfileComponent)
+        .insert(UiCameraConfig { show_ui: true });
+
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            sprite: TextureAtlasSprite {
+                color: BTN_COLOR,
+                custom_size: Some(Vec2::ONE),
+                index: BTN_ESC_IDX,
+                ..Default::default()
+            },
+            texture_atlas: sheets.keys.clone(),
+            transform: Transform::from_translation(Vec3::Z),
+            ..Default::default()
+        })
+        .insert(Button)
+        .insert(ProfileComponent);
+
+    let status = if player.name.is_empty() {
+        TXT_NO_NAME
+    } else {
+        client.send_message(
+            DefaultChannels::UnorderedReliable,
+            &StatsCmd::new(player.name.clone()),
+        );
+
+        TXT_LOAD
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            color: Color::NONE.into(),
+            style: Style {
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::ColumnReverse,
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|p| {
+            p.spawn_bundle(NodeBundle {
+                color: Color::BLACK.into(),
+                style: Style {
+                    justify_content: JustifyContent::Center,
+                    size: Size::new(Val::Percent(100.), Val::Px(TITLE_SIZE)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|p| {
+                p.spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        TITLE_TEXT,
+                        TextStyle {
+                            color: TITLE_COLOR,
+                            font: fonts.bold.clone(),
+                            font_size: TITLE_SIZE,
+                        },
+                    ),
+                    ..Default::default()
+                });
+            });
+
+            p.spawn_bundle(TextBundle {
+                style: Style {
+                    margin: UiRect::all(Val::Px(ENTRY_SIZE)),
+                    ..Default::default()
+                },
+                text: Text::from_sections(vec![
+                    section(&fonts, NAME_COLOR, format!("{}\n\n", player.name)),
+                    section(&fonts, LABEL_COLOR, status.to_string()),
+                ]),
+                ..Default::default()
+            })
+            .insert(StatsText);
+        })
+        .insert(ProfileComponent);
+}
+
+fn stats_message(
+    mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
+    fonts: Res<FontAssets>,
+    player: Res<Player>,
+    mut query: Query<&mut Text, With<StatsText>>,
+) {
+    for event in event_reader.iter() {
+        if let MessageEvent(_, Protocol::StatsMsg(msg)) = event {
+            if *msg.name != player.name {
+                continue;
+            }
+
+            let games = *msg.games;
+            let average = match games {
+                0 => 0.,
+                _ => *msg.score as f32 / games as f32,
+            };
+
+            let survival = *msg.survival;
+            let entries = vec![
+                (TXT_GAMES, format!("{}", games)),
+                (TXT_FOOD, format!("{}", *msg.food)),
+                (TXT_AVERAGE, format!("{:.1}", average)),
+                (
+                    TXT_SURVIVAL,
+                    format!("{}:{:02}", survival / 60, survival % 60),
+                ),
+                (TXT_DEATHS_SELF, format!("{}", *msg.deaths_self)),
+                (TXT_DEATHS_SNAKE, format!("{}", *msg.deaths_snake)),
+                (TXT_DEATHS_QUIT, format!("{}", *msg.deaths_quit)),
+            ];
+
+            let mut txt = query.single_mut();
+            txt.sections.truncate(1);
+            for (label, value) in entries {
+                txt.sections
+                    .push(section(&fonts, LABEL_COLOR, format!("{: <20}", label)));
+                txt.sections
+                    .push(section(&fonts, VALUE_COLOR, format!("{: >6}\n", value)));
+            }
+        }
+    }
+}
+
+fn update_buttons(
+    mut buttons: Query<(&mut TextureAtlasSprite, &mut Transform), With<Button>>,
+    input_state: Res<InputState>,
+    windows: Res<Windows>,
+) {
+    let wnd = windows.get_primary().unwrap();
+    let wnd_sze = Vec2::new(wnd.width(), wnd.height());
+    let blk = wnd_sze.min_element() / (GRID_SIZE + 1) as f32;
+    let offs = 0.5 * wnd_sze;
+    let (mut tex, mut tf) = buttons.single_mut();
+
+    tex.color = match *input_state {
+        InputState::Gamepad | InputState::Keyboard => Color::NONE,
+        InputState::Mouse | InputState::Touch => BTN_COLOR,
+    };
+
+    tf.scale = Vec2::splat(2. * blk).extend(tf.scale.z);
+    tf.translation = (1.5 * blk - offs).extend(tf.translation.z);
+}
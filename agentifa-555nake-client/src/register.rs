@@ -1,10 +1,12 @@
+use agentifa_555nake_protocol::protocol::{RejectReason, NAME_SIZE};
 use bevy::{
     core_pipeline::clear_color::ClearColor,
     input::Input,
     prelude::{
-        App, BuildChildren, Camera2dBundle, Color, Commands, Component, DespawnRecursiveExt,
-        Entity, EventReader, KeyCode, NodeBundle, ParallelSystemDescriptorCoercion, Plugin, Query,
-        Res, ResMut, State, SystemSet, TextBundle, UiCameraConfig, With,
+        App, BuildChildren, ButtonBundle, Camera2dBundle, Changed, Color, Commands, Component,
+        DespawnRecursiveExt, Entity, EventReader, Interaction, KeyCode, NodeBundle,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut, State, SystemSet, TextBundle,
+        UiCameraConfig, With, Without,
     },
     text::{HorizontalAlign, Text, TextAlignment, TextStyle, VerticalAlign},
     ui::{AlignItems, JustifyContent, Size, Style, Val},
@@ -12,12 +14,29 @@ use bevy::{
 };
 
 use crate::{
+    bindings::{Action, Bindings},
+    gamepad::{Pad, PadButton},
+    room::RoomList,
+    storage,
     vkeyboard::{Button, Key},
-    AppState, FontAssets, InputState, Player,
+    AppState, FontAssets, InputState, Player, NAME_KEY,
 };
 
+const FIELD_CLR: Color = Color::GRAY;
+const FIELD_CLR_FOCUS: Color = Color::PINK;
 const FNTSZE: f32 = 30.0;
-const NAMESZE: usize = 30;
+const FNTSZE_ROOMS: f32 = 20.0;
+const REJECT_TXT_BLOCKED: &str = "Agent ID is not allowed";
+const REJECT_TXT_EMPTY: &str = "Agent ID must not be empty";
+const REJECT_TXT_LONG: &str = "Agent ID is too long";
+const REJECT_TXT_ROOM_CODE: &str = "Invite code is not valid";
+const REJECT_TXT_ROOM_INVALID: &str = "Room name is not allowed";
+const REJECT_TXT_ROOM_PRIVATE: &str = "Room is private, enter its #code";
+const REJECT_TXT_ROOM_STARTED: &str = "Room is already playing";
+const REJECT_TXT_TAKEN: &str = "Agent ID is already taken";
+const REJECT_TXT_TOKEN: &str = "Invalid agent token";
+const ROOM_TXT: &str = "Room or #code (empty: public game):";
+const ROOMS_TXT: &str = "Open rooms:";
 
 pub struct RegisterPlugin;
 impl Plugin for RegisterPlugin {
@@ -26,27 +45,103 @@ impl Plugin for RegisterPlugin {
             .add_system_set(SystemSet::on_exit(AppState::Register).with_system(cleanup))
             .add_system_set(
                 SystemSet::on_update(AppState::Register)
+                    .with_system(input_button)
+                    .with_system(input_gamepad.after(InputState::Gamepad))
                     .with_system(input_keyboard.after(InputState::Keyboard))
                     .with_system(input_vkeyboard)
                     .with_system(update_text),
-            );
+            )
+            .insert_resource(Focus(Field::Name));
     }
 }
 
+/// Input field that receives the keyboard
+#[derive(Clone, Component, Copy, PartialEq)]
+enum Field {
+    Name,
+    Room,
+}
+
+impl Field {
+    fn next(&self) -> Field {
+        match self {
+            Field::Name => Field::Room,
+            Field::Room => Field::Name,
+        }
+    }
+
+    fn input<'a>(&self, player: &'a mut Player) -> &'a mut String {
+        match self {
+            Field::Name => &mut player.name,
+            Field::Room => &mut player.room,
+        }
+    }
+}
+
+struct Focus(Field);
+
 #[derive(Component)]
 struct RegisterComponent;
 
+#[derive(Component)]
+struct RejectText;
+
+#[derive(Component)]
+struct RoomsText;
+
 #[derive(Component)]
 struct TextInput;
 
-fn cleanup(mut commands: Commands, query: Query<Entity, With<RegisterComponent>>) {
+fn cleanup(
+    mut commands: Commands,
+    player: Res<Player>,
+    query: Query<Entity, With<RegisterComponent>>,
+) {
+    storage::save(NAME_KEY, &player.name);
     for entity in query.iter() {
         commands.entity(entity).despawn_recursive();
     }
 }
 
+fn input_button(
+    mut focus: ResMut<Focus>,
+    query: Query<(&Field, &Interaction), Changed<Interaction>>,
+) {
+    for (field, interaction) in query.iter() {
+        if *interaction == Interaction::Clicked {
+            focus.0 = *field;
+        }
+    }
+}
+
+fn input_gamepad(
+    mut app_state: ResMut<State<AppState>>,
+    mut focus: ResMut<Focus>,
+    input_state: Res<InputState>,
+    pad: Res<Pad>,
+    mut player: ResMut<Player>,
+) {
+    if !vec![InputState::Gamepad].contains(&input_state) {
+        return;
+    }
+
+    if pad.just_pressed(PadButton::East) {
+        focus.0.input(&mut player).pop();
+    }
+
+    if pad.just_pressed(PadButton::Start) {
+        app_state.set(AppState::Game).unwrap();
+    }
+
+    if pad.just_pressed(PadButton::West) {
+        focus.0 = focus.0.next();
+    }
+}
+
 fn input_keyboard(
     mut app_state: ResMut<State<AppState>>,
+    bindings: Res<Bindings>,
+    mut focus: ResMut<Focus>,
     mut input: ResMut<Input<KeyCode>>,
     mut input_char: EventReader<ReceivedCharacter>,
     input_state: Res<InputState>,
@@ -56,10 +151,14 @@ fn input_keyboard(
         return;
     }
 
-    let name = &mut player.name;
-    if input.pressed(KeyCode::Back) {
-        input.release(KeyCode::Back);
+    if bindings.take(&mut input, Action::Switch) {
+        focus.0 = focus.0.next();
+
+        return;
+    }
 
+    let name = focus.0.input(&mut player);
+    if bindings.take(&mut input, Action::Delete) {
         if name.len() > 0 {
             name.pop().unwrap();
         }
@@ -67,14 +166,13 @@ fn input_keyboard(
         return;
     }
 
-    if input.pressed(KeyCode::Return) {
-        input.release(KeyCode::Return);
+    if bindings.take(&mut input, Action::Confirm) {
         app_state.set(AppState::Game).unwrap();
         return;
     }
 
     for e in input_char.iter() {
-        if !e.char.is_control() && name.len() <= NAMESZE {
+        if !e.char.is_control() && name.chars().count() < NAME_SIZE {
             name.push(e.char);
         }
     }
@@ -83,10 +181,11 @@ fn input_keyboard(
 fn input_vkeyboard(
     mut app_state: ResMut<State<AppState>>,
     mut event_reader: EventReader<Button>,
+    focus: Res<Focus>,
     mut player: ResMut<Player>,
 ) {
     for btn in event_reader.iter() {
-        let name = &mut player.name;
+        let name = focus.0.input(&mut player);
 
         match btn.key {
             Key::Backspace => {
@@ -97,18 +196,49 @@ fn input_vkeyboard(
             Key::Return => {
                 app_state.set(AppState::Game).unwrap();
             }
-            _ => name.push_str(btn.to_string().as_str()),
+            _ => {
+                if name.chars().count() < NAME_SIZE {
+                    name.push_str(btn.to_string().as_str());
+                }
+            }
         }
     }
 }
 
-fn setup(mut clear: ResMut<ClearColor>, mut commands: Commands, fonts: Res<FontAssets>) {
+fn setup(
+    mut clear: ResMut<ClearColor>,
+    mut commands: Commands,
+    mut focus: ResMut<Focus>,
+    fonts: Res<FontAssets>,
+    player: Res<Player>,
+) {
     clear.0 = Color::BLACK;
+    focus.0 = match player.reject {
+        Some(
+            RejectReason::RoomCodeInvalid
+            | RejectReason::RoomInvalid
+            | RejectReason::RoomPrivate
+            | RejectReason::RoomStarted,
+        ) => Field::Room,
+        _ => Field::Name,
+    };
+
     commands
         .spawn_bundle(Camera2dBundle::default())
         .insert(RegisterComponent)
         .insert(UiCameraConfig { show_ui: true });
 
+    let alignment = TextAlignment {
+        horizontal: HorizontalAlign::Center,
+        vertical: VerticalAlign::Center,
+    };
+
+    let style = |color: Color, font_size: f32| TextStyle {
+        color,
+        font: fonts.regular.clone(),
+        font_size,
+    };
+
     commands
         .spawn_bundle(NodeBundle {
             color: Color::NONE.into(),
@@ -122,49 +252,94 @@ fn setup(mut clear: ResMut<ClearColor>, mut commands: Commands, fonts: Res<FontA
             ..Default::default()
         })
         .with_children(|p| {
+            for (field, label) in [
+                (Field::Name, "Enter your Agent ID:"),
+                (Field::Room, ROOM_TXT),
+            ] {
+                p.spawn_bundle(TextBundle {
+                    text: Text::from_section(label, style(Color::CYAN, FNTSZE))
+                        .with_alignment(alignment),
+                    ..Default::default()
+                });
+
+                p.spawn_bundle(ButtonBundle {
+                    color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|p| {
+                    p.spawn_bundle(TextBundle {
+                        text: Text::from_section("", style(FIELD_CLR, FNTSZE))
+                            .with_alignment(alignment),
+                        ..Default::default()
+                    })
+                    .insert(field)
+                    .insert(TextInput);
+                })
+                .insert(field);
+            }
+
             p.spawn_bundle(TextBundle {
-                text: Text::from_section(
-                    "Enter your Agent ID:",
-                    TextStyle {
-                        color: Color::CYAN,
-                        font: fonts.regular.clone(),
-                        font_size: FNTSZE,
-                    },
-                )
-                .with_alignment(TextAlignment {
-                    horizontal: HorizontalAlign::Center,
-                    vertical: VerticalAlign::Center,
-                }),
+                text: Text::from_section("", style(Color::RED, FNTSZE)).with_alignment(alignment),
                 ..Default::default()
-            });
+            })
+            .insert(RejectText);
 
             p.spawn_bundle(TextBundle {
-                text: Text::from_section(
-                    "",
-                    TextStyle {
-                        color: Color::PINK,
-                        font: fonts.regular.clone(),
-                        font_size: FNTSZE,
-                    },
-                )
-                .with_alignment(TextAlignment {
-                    horizontal: HorizontalAlign::Center,
-                    vertical: VerticalAlign::Center,
-                }),
+                text: Text::from_section("", style(Color::CYAN, FNTSZE_ROOMS))
+                    .with_alignment(alignment),
                 ..Default::default()
             })
-            .insert(TextInput);
+            .insert(RoomsText);
         })
         .insert(RegisterComponent);
 }
 
-fn update_text(player: Res<Player>, mut text: Query<&mut Text, With<TextInput>>) {
-    let mut txt = text.iter_mut().next().unwrap();
-    let mut lbl = player.name.clone();
+fn update_text(
+    focus: Res<Focus>,
+    list: Res<RoomList>,
+    player: Res<Player>,
+    mut reject: Query<&mut Text, (With<RejectText>, Without<RoomsText>, Without<TextInput>)>,
+    mut rooms: Query<&mut Text, (With<RoomsText>, Without<RejectText>, Without<TextInput>)>,
+    mut text: Query<
+        (&Field, &mut Text),
+        (With<TextInput>, Without<RejectText>, Without<RoomsText>),
+    >,
+) {
+    for (field, mut txt) in text.iter_mut() {
+        let mut lbl = match field {
+            Field::Name => player.name.clone(),
+            Field::Room => player.room.clone(),
+        };
+
+        if lbl.len() <= 0 {
+            lbl = " ".to_string();
+        }
+
+        txt.sections[0].value = lbl;
+        txt.sections[0].style.color = match *field == focus.0 {
+            true => FIELD_CLR_FOCUS,
+            false => FIELD_CLR,
+        };
+    }
 
-    if lbl.len() <= 0 {
-        lbl = " ".to_string();
+    let mut txt = reject.iter_mut().next().unwrap();
+    txt.sections[0].value = match player.reject {
+        Some(RejectReason::NameBlocked) => REJECT_TXT_BLOCKED,
+        Some(RejectReason::NameEmpty) => REJECT_TXT_EMPTY,
+        Some(RejectReason::NameTaken) => REJECT_TXT_TAKEN,
+        Some(RejectReason::NameTooLong) => REJECT_TXT_LONG,
+        Some(RejectReason::RoomCodeInvalid) => REJECT_TXT_ROOM_CODE,
+        Some(RejectReason::RoomInvalid) => REJECT_TXT_ROOM_INVALID,
+        Some(RejectReason::RoomPrivate) => REJECT_TXT_ROOM_PRIVATE,
+        Some(RejectReason::RoomStarted) => REJECT_TXT_ROOM_STARTED,
+        Some(RejectReason::TokenInvalid) => REJECT_TXT_TOKEN,
+        None => "",
     }
+    .to_string();
 
-    txt.sections[0].value = lbl;
+    let mut txt = rooms.iter_mut().next().unwrap();
+    txt.sections[0].value = match list.names.is_empty() {
+        true => String::new(),
+        false => format!("{} {}", ROOMS_TXT, list.names.join(", ")),
+    };
 }
@@ -0,0 +1,346 @@
+use agentifa_555nake_protocol::protocol::{Protocol, Room, RoomAction, RoomCmd};
+use bevy::{
+    input::Input,
+    math::Vec3,
+    prelude::{
+        App, BuildChildren, ButtonBundle, Changed, Color, Commands, Component, DespawnRecursiveExt,
+        Entity, EventReader, Interaction, KeyCode, MouseButton, ParallelSystemDescriptorCoercion,
+        Plugin, Query, Res, ResMut, SystemSet, TextBundle, Transform, Visibility, With,
+    },
+    text::{Text, TextAlignment, TextStyle},
+    ui::{AlignItems, FlexDirection, JustifyContent, Size, Style, UiRect, Val},
+};
+use naia_bevy_client::{events::MessageEvent, shared::DefaultChannels, Client};
+
+use crate::{
+    bindings::{Action, Bindings},
+    chat::Chat,
+    gamepad::{Pad, PadButton},
+    AppState, FontAssets, InputState, Player,
+};
+
+const ROOM_BG: Color = Color::rgba(0., 0., 0., 0.75);
+const ROOM_BTN_BG: Color = Color::rgba(1., 1., 1., 0.25);
+const ROOM_CLR: Color = Color::YELLOW;
+const ROOM_PAD: f32 = 10.0; // Px
+const ROOM_SZE: f32 = 24.0; // Font Size
+const ROOM_TXT_CODE: &str = "Einladungscode:";
+const ROOM_TXT_LOCK: &str = "L / Klick: privat";
+const ROOM_TXT_OWNER: &str = "P / Mittelklick: Pause";
+const ROOM_TXT_PAUSE: &str = "Pause";
+const ROOM_TXT_READY: &str = "R / Klick: bereit";
+const ROOM_TXT_RESUME: &str = "P / Klick: weiter";
+const ROOM_TXT_UNLOCK: &str = "L / Klick: öffentlich";
+
+pub struct RoomPlugin;
+impl Plugin for RoomPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Game).with_system(setup))
+            .add_system_set(SystemSet::on_exit(AppState::Game).with_system(cleanup))
+            .add_system_set(
+                SystemSet::on_update(AppState::Game)
+                    .with_system(input_button)
+                    .with_system(input_gamepad.after(InputState::Gamepad))
+                    .with_system(input_keyboard.after(InputState::Keyboard))
+                    .with_system(input_mouse.after(InputState::Mouse))
+                    .with_system(update_overlay),
+            )
+            .add_system(rooms_message)
+            .insert_resource(RoomList { names: Vec::new() });
+    }
+}
+
+/// Private rooms that wait for players
+pub struct RoomList {
+    pub names: Vec<String>,
+}
+
+#[derive(Clone, Component, Copy)]
+enum RoomButton {
+    Lock,
+    Overlay,
+}
+
+#[derive(Component)]
+struct RoomComponent;
+
+/// Lock button of the owner
+#[derive(Component)]
+struct RoomLock;
+
+#[derive(Component)]
+struct RoomOverlay;
+
+#[derive(Component)]
+struct RoomText;
+
+/// Action the `key` of the player stands for: ready toggle in the lobby,
+/// lock, pause and resume for the owner
+fn action(room: &Room, player: &Player, key: RoomAction) -> Option<RoomAction> {
+    let owner = *room.owner == player.name;
+    match key {
+        RoomAction::Lock if owner => Some(RoomAction::Lock),
+        RoomAction::Pause if owner && *room.started => match *room.paused {
+            true => Some(RoomAction::Resume),
+            false => Some(RoomAction::Pause),
+        },
+        RoomAction::Ready if !*room.started => Some(RoomAction::Ready),
+        _ => None,
+    }
+}
+
+fn cleanup(mut commands: Commands, query: Query<Entity, With<RoomComponent>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn input_button(
+    chat: Res<Chat>,
+    mut client: Client<Protocol, DefaultChannels>,
+    player: Res<Player>,
+    query: Query<(&RoomButton, &Interaction), Changed<Interaction>>,
+    rooms: Query<&Room>,
+) {
+    if chat.open {
+        return;
+    }
+
+    for (btn, interaction) in query.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        let key = |room: &Room| match btn {
+            RoomButton::Lock => RoomAction::Lock,
+            RoomButton::Overlay if *room.paused => RoomAction::Pause,
+            RoomButton::Overlay => RoomAction::Ready,
+        };
+
+        if let Some(action) = rooms.iter().find_map(|r| action(r, &player, key(r))) {
+            client.send_message(DefaultChannels::UnorderedReliable, &RoomCmd::new(action));
+        }
+    }
+}
+
+fn input_gamepad(
+    chat: Res<Chat>,
+    mut client: Client<Protocol, DefaultChannels>,
+    input_state: Res<InputState>,
+    pad: Res<Pad>,
+    player: Res<Player>,
+    rooms: Query<&Room>,
+) {
+    if chat.open || !vec![InputState::Gamepad].contains(&input_state) {
+        return;
+    }
+
+    let key = match [PadButton::East, PadButton::North, PadButton::West]
+        .into_iter()
+        .find(|b| pad.just_pressed(*b))
+    {
+        Some(PadButton::East) => RoomAction::Lock,
+        Some(PadButton::North) => RoomAction::Ready,
+        Some(PadButton::West) => RoomAction::Pause,
+        _ => return,
+    };
+
+    if let Some(action) = rooms.iter().find_map(|r| action(r, &player, key)) {
+        client.send_message(DefaultChannels::UnorderedReliable, &RoomCmd::new(action));
+    }
+}
+
+fn input_keyboard(
+    bindings: Res<Bindings>,
+    chat: Res<Chat>,
+    mut client: Client<Protocol, DefaultChannels>,
+    mut input: ResMut<Input<KeyCode>>,
+    input_state: Res<InputState>,
+    player: Res<Player>,
+    rooms: Query<&Room>,
+) {
+    if chat.open || !vec![InputState::Keyboard].contains(&input_state) {
+        return;
+    }
+
+    let key = match [Action::Lock, Action::Pause, Action::Ready]
+        .into_iter()
+        .find(|a| bindings.just_pressed(&input, *a))
+    {
+        Some(Action::Lock) => RoomAction::Lock,
+        Some(Action::Pause) => RoomAction::Pause,
+        Some(Action::Ready) => RoomAction::Ready,
+        _ => return,
+    };
+
+    for action in [Action::Lock, Action::Pause, Action::Ready] {
+        bindings.reset(&mut input, action);
+    }
+    if let Some(action) = rooms.iter().find_map(|r| action(r, &player, key)) {
+        client.send_message(DefaultChannels::UnorderedReliable, &RoomCmd::new(action));
+    }
+}
+
+fn input_mouse(
+    chat: Res<Chat>,
+    mut client: Client<Protocol, DefaultChannels>,
+    input: Res<Input<MouseButton>>,
+    input_state: Res<InputState>,
+    player: Res<Player>,
+    rooms: Query<&Room>,
+) {
+    if chat.open
+        || !vec![InputState::Mouse].contains(&input_state)
+        || !input.just_pressed(MouseButton::Middle)
+    {
+        return;
+    }
+
+    if let Some(action) = rooms
+        .iter()
+        .find_map(|r| action(r, &player, RoomAction::Pause))
+    {
+        client.send_message(DefaultChannels::UnorderedReliable, &RoomCmd::new(action));
+    }
+}
+
+fn rooms_message(
+    mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
+    mut list: ResMut<RoomList>,
+) {
+    for event in event_reader.iter() {
+        if let MessageEvent(_, Protocol::RoomsMsg(msg)) = event {
+            list.names = (*msg.names).clone();
+        }
+    }
+}
+
+fn setup(mut commands: Commands, fonts: Res<FontAssets>) {
+    commands
+        .spawn_bundle(ButtonBundle {
+            color: ROOM_BG.into(),
+            style: Style {
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(3. * Vec3::Z),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .with_children(|p| {
+            let style = TextStyle {
+                color: ROOM_CLR,
+                font: fonts.bold.clone(),
+                font_size: ROOM_SZE,
+            };
+
+            p.spawn_bundle(TextBundle {
+                text: Text::from_section("", style.clone()).with_alignment(TextAlignment::CENTER),
+                visibility: Visibility { is_visible: false },
+                ..Default::default()
+            })
+            .insert(RoomOverlay)
+            .insert(RoomText);
+
+            p.spawn_bundle(ButtonBundle {
+                color: ROOM_BTN_BG.into(),
+                style: Style {
+                    margin: UiRect::all(Val::Px(ROOM_PAD)),
+                    padding: UiRect::all(Val::Px(ROOM_PAD)),
+                    ..Default::default()
+                },
+                visibility: Visibility { is_visible: false },
+                ..Default::default()
+            })
+            .with_children(|p| {
+                p.spawn_bundle(TextBundle {
+                    text: Text::from_section("", style),
+                    visibility: Visibility { is_visible: false },
+                    ..Default::default()
+                })
+                .insert(RoomLock);
+            })
+            .insert(RoomButton::Lock)
+            .insert(RoomLock);
+        })
+        .insert(RoomButton::Overlay)
+        .insert(RoomComponent)
+        .insert(RoomOverlay);
+}
+
+/// Shows the ready list and the invite code before the round starts and the
+/// pause overlay
+fn update_overlay(
+    mut locks: Query<&mut Visibility, (With<RoomLock>, Without<RoomOverlay>)>,
+    mut lock_texts: Query<&mut Text, (With<RoomLock>, Without<RoomText>)>,
+    mut overlays: Query<&mut Visibility, (With<RoomOverlay>, Without<RoomLock>)>,
+    player: Res<Player>,
+    rooms: Query<&Room>,
+    mut texts: Query<&mut Text, (With<RoomText>, Without<RoomLock>)>,
+) {
+    let room = rooms.iter().next();
+    let lock = match room {
+        Some(room) if !*room.started && *room.owner == player.name => match room.code.is_empty() {
+            true => ROOM_TXT_LOCK,
+            false => ROOM_TXT_UNLOCK,
+        },
+        _ => "",
+    };
+
+    let lbl = match room {
+        Some(room) if !*room.started => {
+            let players: Vec<String> = room
+                .players
+                .iter()
+                .map(|name| match room.ready.contains(name) {
+                    true => format!("[x] {}", name),
+                    false => format!("[ ] {}", name),
+                })
+                .collect();
+
+            let mut lbl = format!(
+                "{}\n\n{}\n\n{}",
+                *room.name,
+                players.join("\n"),
+                ROOM_TXT_READY
+            );
+
+            if !room.code.is_empty() {
+                lbl = format!("{} #{}\n\n{}", ROOM_TXT_CODE, *room.code, lbl);
+            }
+
+            match *room.owner == player.name {
+                true => format!("{}\n{}", lbl, ROOM_TXT_OWNER),
+                false => lbl,
+            }
+        }
+        Some(room) if *room.paused && *room.owner == player.name => {
+            format!("{}\n\n{}", ROOM_TXT_PAUSE, ROOM_TXT_RESUME)
+        }
+        Some(room) if *room.paused => ROOM_TXT_PAUSE.to_string(),
+        _ => String::new(),
+    };
+
+    for mut visibility in overlays.iter_mut() {
+        visibility.is_visible = !lbl.is_empty();
+    }
+
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != lbl {
+            text.sections[0].value = lbl.clone();
+        }
+    }
+
+    for mut visibility in locks.iter_mut() {
+        visibility.is_visible = !lock.is_empty();
+    }
+
+    for mut text in lock_texts.iter_mut() {
+        if text.sections[0].value != lock {
+            text.sections[0].value = lock.to_string();
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use agentifa_555nake_protocol::protocol::Protocol;
+use bevy::{
+    core_pipeline::clear_color::ClearColor,
+    input::Input,
+    prelude::{
+        App, BuildChildren, Camera2dBundle, Color, Commands, Component, DespawnRecursiveExt,
+        Entity, EventReader, KeyCode, NodeBundle, ParallelSystemDescriptorCoercion, Plugin, Query,
+        Res, ResMut, State, SystemSet, TextBundle, UiCameraConfig, With, Without,
+    },
+    text::{HorizontalAlign, Text, TextAlignment, TextStyle, VerticalAlign},
+    ui::{AlignItems, JustifyContent, Size, Style, Val},
+    window::ReceivedCharacter,
+};
+use naia_bevy_client::{shared::DefaultChannels, Client};
+
+use crate::{
+    bindings::{Action, Bindings},
+    retry_connection,
+    vkeyboard::{Button, Key},
+    AppState, Connection, FontAssets, InputState, NetState, Server,
+};
+
+const FNTSZE: f32 = 30.0;
+const INVALID_TXT: &str = "Expected <prot>://<addr>:<port>";
+const TITLE_TXT: &str = "Enter the server URL:";
+const URL_SIZE: usize = 100;
+
+pub struct ServerPlugin;
+impl Plugin for ServerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Server).with_system(setup))
+            .add_system_set(SystemSet::on_exit(AppState::Server).with_system(cleanup))
+            .add_system_set(
+                SystemSet::on_update(AppState::Server)
+                    .with_system(input_keyboard.after(InputState::Keyboard))
+                    .with_system(input_vkeyboard)
+                    .with_system(update_text),
+            )
+            .insert_resource(ServerInput::default());
+    }
+}
+
+#[derive(Default)]
+struct ServerInput {
+    invalid: bool,
+    url: String,
+}
+
+#[derive(Component)]
+struct InvalidText;
+
+#[derive(Component)]
+struct ServerComponent;
+
+#[derive(Component)]
+struct TextInput;
+
+/// Switches to the entered server and connects to it.
+fn apply(
+    app_state: &mut State<AppState>,
+    client: &mut Client<Protocol, DefaultChannels>,
+    connection: &mut Connection,
+    input: &mut ServerInput,
+    net_state: &mut State<NetState>,
+    server: &mut Server,
+) {
+    match Server::parse(&input.url) {
+        Some(entry) => {
+            *server = entry;
+            server.save();
+            retry_connection(client, connection, net_state, server);
+            app_state.set(AppState::Connect).unwrap();
+        }
+        None => input.invalid = true,
+    }
+}
+
+fn cleanup(mut commands: Commands, query: Query<Entity, With<ServerComponent>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn input_keyboard(
+    mut app_state: ResMut<State<AppState>>,
+    bindings: Res<Bindings>,
+    mut client: Client<Protocol, DefaultChannels>,
+    mut connection: ResMut<Connection>,
+    mut input: ResMut<Input<KeyCode>>,
+    mut input_char: EventReader<ReceivedCharacter>,
+    input_state: Res<InputState>,
+    mut net_state: ResMut<State<NetState>>,
+    mut server: ResMut<Server>,
+    mut server_input: ResMut<ServerInput>,
+) {
+    if !vec![InputState::Keyboard].contains(&input_state) {
+        return;
+    }
+
+    if bindings.take(&mut input, Action::Back) {
+        app_state.set(AppState::Connect).unwrap();
+        return;
+    }
+
+    if bindings.take(&mut input, Action::Delete) {
+        server_input.url.pop();
+        server_input.invalid = false;
+        return;
+    }
+
+    if bindings.take(&mut input, Action::Confirm) {
+        apply(
+            &mut app_state,
+            &mut client,
+            &mut connection,
+            &mut server_input,
+            &mut net_state,
+            &mut server,
+        );
+
+        return;
+    }
+
+    for e in input_char.iter() {
+        if !e.char.is_control() && server_input.url.chars().count() < URL_SIZE {
+            server_input.url.push(e.char);
+            server_input.invalid = false;
+        }
+    }
+}
+
+fn input_vkeyboard(
+    mut app_state: ResMut<State<AppState>>,
+    mut client: Client<Protocol, DefaultChannels>,
+    mut connection: ResMut<Connection>,
+    mut event_reader: EventReader<Button>,
+    mut net_state: ResMut<State<NetState>>,
+    mut server: ResMut<Server>,
+    mut server_input: ResMut<ServerInput>,
+) {
+    for btn in event_reader.iter() {
+        match btn.key {
+            Key::Backspace => {
+                server_input.url.pop();
+                server_input.invalid = false;
+            }
+            Key::Return => {
+                apply(
+                    &mut app_state,
+                    &mut client,
+                    &mut connection,
+                    &mut server_input,
+                    &mut net_state,
+                    &mut server,
+                );
+            }
+            _ => {
+                if server_input.url.chars().count() < URL_SIZE {
+                    server_input.url.push_str(btn.to_string().as_str());
+                    server_input.invalid = false;
+                }
+            }
+        }
+    }
+}
+
+fn setup(
+    mut clear: ResMut<ClearColor>,
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    server: Res<Server>,
+    mut server_input: ResMut<ServerInput>,
+) {
+    server_input.invalid = false;
+    server_input.url = server.url();
+
+    clear.0 = Color::BLACK;
+    commands
+        .spawn_bundle(Camera2dBundle::default())
+        .insert(ServerComponent)
+        .insert(UiCameraConfig { show_ui: true });
+
+    let text = |value: &str, color: Color| TextBundle {
+        text: Text::from_section(
+            value,
+            TextStyle {
+                color,
+                font: fonts.regular.clone(),
+                font_size: FNTSZE,
+            },
+        )
+        .with_alignment(TextAlignment {
+            horizontal: HorizontalAlign::Center,
+            vertical: VerticalAlign::Center,
+        }),
+        ..Default::default()
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            color: Color::NONE.into(),
+            style: Style {
+                align_items: AlignItems::Center,
+                flex_direction: bevy::ui::FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::FlexStart,
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|p| {
+            p.spawn_bundle(text(TITLE_TXT, Color::CYAN));
+            p.spawn_bundle(text("", Color::PINK)).insert(TextInput);
+            p.spawn_bundle(text("", Color::RED)).insert(InvalidText);
+        })
+        .insert(ServerComponent);
+}
+
+fn update_text(
+    mut invalid: Query<&mut Text, (With<InvalidText>, Without<TextInput>)>,
+    server_input: Res<ServerInput>,
+    mut text: Query<&mut Text, (With<TextInput>, Without<InvalidText>)>,
+) {
+    for mut txt in text.iter_mut() {
+        txt.sections[0].value = match server_input.url.is_empty() {
+            true => " ".to_string(),
+            false => server_input.url.clone(),
+        };
+    }
+
+    for mut txt in invalid.iter_mut() {
+        txt.sections[0].value = match server_input.invalid {
+            true => INVALID_TXT,
+            false => "",
+        }
+        .to_string();
+    }
+}
@@ -0,0 +1,520 @@
+use agentifa_555nake_protocol::protocol::Direction;
+use bevy::{
+    core_pipeline::clear_color::ClearColor,
+    input::Input,
+    prelude::{
+        App, BuildChildren, ButtonBundle, Camera2dBundle, Changed, Color, Commands, Component,
+        DespawnRecursiveExt, Entity, Interaction, KeyCode, Local, MouseButton, NodeBundle,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut, State, SystemSet, TextBundle,
+        UiCameraConfig, With,
+    },
+    text::{Text, TextStyle},
+    ui::{AlignItems, FlexDirection, JustifyContent, Size, Style, UiRect, Val},
+    window::{WindowMode, Windows},
+};
+use bevy_kira_audio::{Audio, AudioChannel, AudioControl};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bindings::{key_name, Action, Bindings, KEYS},
+    gamepad::{Pad, PadButton},
+    storage, AppState, FontAssets, InputState, Sfx, WND_SZE_X, WND_SZE_Y,
+};
+
+const ENTRY_PAD: f32 = 1.; // Px
+const ENTRY_SIZE: f32 = 18.; // Font Size
+const LABEL_COLOR: Color = Color::CYAN;
+const OPTIONS_KEY: &str = "options";
+const SELECT_COLOR: Color = Color::PINK;
+const TITLE_COLOR: Color = Color::YELLOW;
+const TITLE_SIZE: f32 = 60.; // Font Size
+const TITLE_TEXT: &str = "SETTINGS";
+const TXT_BACK: &str = "Back";
+const TXT_FULLSCREEN: &str = "Fullscreen";
+const TXT_MUSIC: &str = "Music";
+const TXT_MUTE: &str = "Mute";
+const TXT_NAMES: &str = "Names";
+const TXT_NONE: &str = "-";
+const TXT_OFF: &str = "off";
+const TXT_ON: &str = "on";
+const TXT_PRESS: &str = "press a key...";
+const TXT_RESET: &str = "Reset keys";
+const TXT_SFX: &str = "Effects";
+const TXT_TEMPO: &str = "Tempo";
+const TXT_WINDOW: &str = "Window";
+const VOLUME_MAX: u8 = 10; // Steps
+const WINDOW_SCALES: [f32; 3] = [1., 1.5, 2.];
+
+pub struct SettingsPlugin;
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Settings).with_system(setup))
+            .add_system_set(SystemSet::on_exit(AppState::Settings).with_system(cleanup))
+            .add_system_set(
+                SystemSet::on_update(AppState::Settings)
+                    .with_system(input_button)
+                    .with_system(input_gamepad.after(InputState::Gamepad))
+                    .with_system(input_keyboard.after(InputState::Keyboard))
+                    .with_system(input_mouse.after(InputState::Mouse))
+                    .with_system(update_rows),
+            )
+            .add_system(apply_options)
+            .insert_resource(Selection::default());
+    }
+}
+
+/// Audio, video and gameplay options. They are saved as JSON in the storage.
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct Options {
+    pub fullscreen: bool,
+    pub music: u8,
+    pub mute: bool,
+    pub names: bool,
+    pub sfx: u8,
+    pub tempo: bool,
+    pub window: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            fullscreen: false,
+            music: VOLUME_MAX,
+            mute: false,
+            names: true,
+            sfx: VOLUME_MAX,
+            tempo: true,
+            window: 0,
+        }
+    }
+}
+
+impl Options {
+    pub fn load() -> Self {
+        storage::load(OPTIONS_KEY)
+            .and_then(|j| serde_json::from_str(&j).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Ok(json) = serde_json::to_string(self) {
+            storage::save(OPTIONS_KEY, &json);
+        }
+    }
+
+    fn volume(&self, steps: u8) -> f64 {
+        match self.mute {
+            true => 0.,
+            false => steps.min(VOLUME_MAX) as f64 / VOLUME_MAX as f64,
+        }
+    }
+
+    fn window_size(&self) -> (f32, f32) {
+        let scale = WINDOW_SCALES.get(self.window).unwrap_or(&1.);
+        (scale * WND_SZE_X, scale * WND_SZE_Y)
+    }
+}
+
+/// Entries of the settings screen in display order
+#[derive(Clone, Copy)]
+enum Row {
+    Back,
+    Fullscreen,
+    Key(Action),
+    Music,
+    Mute,
+    Names,
+    Reset,
+    Sfx,
+    Tempo,
+    Window,
+}
+
+impl Row {
+    /// Changes the option of the row by `step`, volumes and window sizes wrap
+    /// around
+    fn adjust(&self, options: &mut Options, step: i32) {
+        let volume = |v: u8| (v as i32 + step).rem_euclid(VOLUME_MAX as i32 + 1) as u8;
+        match self {
+            Row::Fullscreen => options.fullscreen = !options.fullscreen,
+            Row::Music => options.music = volume(options.music),
+            Row::Mute => options.mute = !options.mute,
+            Row::Names => options.names = !options.names,
+            Row::Sfx => options.sfx = volume(options.sfx),
+            Row::Tempo => options.tempo = !options.tempo,
+            Row::Window => {
+                let count = WINDOW_SCALES.len() as i32;
+                options.window = (options.window as i32 + step).rem_euclid(count) as usize;
+            }
+            Row::Back | Row::Key(_) | Row::Reset => return,
+        }
+
+        options.save();
+    }
+
+    fn all() -> Vec<Row> {
+        let mut rows = vec![
+            Row::Music,
+            Row::Sfx,
+            Row::Mute,
+            Row::Tempo,
+            Row::Fullscreen,
+            Row::Window,
+            Row::Names,
+        ];
+
+        rows.extend(Action::ALL.iter().map(|a| Row::Key(*a)));
+        rows.push(Row::Reset);
+        rows.push(Row::Back);
+        rows
+    }
+
+    fn label(&self, bindings: &Bindings, options: &Options, waiting: bool) -> String {
+        let slider = |label: &str, value: u8| {
+            let value = value.min(VOLUME_MAX) as usize;
+            let rest = VOLUME_MAX as usize - value;
+            format!("{: <12}[{}{}]", label, "#".repeat(value), "-".repeat(rest))
+        };
+
+        let toggle = |label: &str, on: bool| match on {
+            true => format!("{: <12}{}", label, TXT_ON),
+            false => format!("{: <12}{}", label, TXT_OFF),
+        };
+
+        match self {
+            Row::Back => TXT_BACK.to_string(),
+            Row::Fullscreen => toggle(TXT_FULLSCREEN, options.fullscreen),
+            Row::Key(action) => {
+                let keys: Vec<&str> = bindings.keys(*action).iter().filter_map(key_name).collect();
+
+                let value = match waiting {
+                    true => TXT_PRESS.to_string(),
+                    false if keys.is_empty() => TXT_NONE.to_string(),
+                    false => keys.join(" / "),
+                };
+
+                format!("{: <12}{}", action.label(), value)
+            }
+            Row::Music => slider(TXT_MUSIC, options.music),
+            Row::Mute => toggle(TXT_MUTE, options.mute),
+            Row::Names => toggle(TXT_NAMES, options.names),
+            Row::Reset => TXT_RESET.to_string(),
+            Row::Sfx => slider(TXT_SFX, options.sfx),
+            Row::Tempo => toggle(TXT_TEMPO, options.tempo),
+            Row::Window => {
+                let (width, height) = options.window_size();
+                format!("{: <12}{}x{}", TXT_WINDOW, width, height)
+            }
+        }
+    }
+}
+
+/// Selected row, `waiting` while the next key press gets bound to it
+#[derive(Default)]
+struct Selection {
+    row: usize,
+    waiting: bool,
+}
+
+#[derive(Component)]
+struct SettingsComponent;
+
+#[derive(Component)]
+struct SettingsRow(usize);
+
+#[derive(Component)]
+struct SettingsText(usize);
+
+fn activate(
+    app_state: &mut State<AppState>,
+    bindings: &mut Bindings,
+    options: &mut Options,
+    selection: &mut Selection,
+) {
+    match Row::all()[selection.row] {
+        Row::Back => app_state.set(AppState::Menu).unwrap(),
+        Row::Key(_) => selection.waiting = true,
+        Row::Reset => {
+            *bindings = Bindings::default();
+            bindings.save();
+        }
+        row => row.adjust(options, 1),
+    }
+}
+
+/// Applies changed options to the audio channels and the window
+fn apply_options(
+    audio: Res<Audio>,
+    options: Res<Options>,
+    sfx: Res<AudioChannel<Sfx>>,
+    mut window: Local<Option<(bool, usize)>>,
+    mut windows: ResMut<Windows>,
+) {
+    if !options.is_changed() {
+        return;
+    }
+
+    audio.set_volume(options.volume(options.music));
+    sfx.set_volume(options.volume(options.sfx));
+
+    // The window may be resized by hand, so only its own options resize it
+    if *window == Some((options.fullscreen, options.window)) {
+        return;
+    }
+
+    if let Some(wnd) = windows.get_primary_mut() {
+        let (width, height) = options.window_size();
+        wnd.set_mode(match options.fullscreen {
+            true => WindowMode::BorderlessFullscreen,
+            false => WindowMode::Windowed,
+        });
+
+        wnd.set_resolution(width, height);
+        *window = Some((options.fullscreen, options.window));
+    }
+}
+
+fn cleanup(mut commands: Commands, query: Query<Entity, With<SettingsComponent>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn input_button(
+    mut app_state: ResMut<State<AppState>>,
+    mut bindings: ResMut<Bindings>,
+    input_state: Res<InputState>,
+    mut options: ResMut<Options>,
+    query: Query<(&SettingsRow, &Interaction), Changed<Interaction>>,
+    mut selection: ResMut<Selection>,
+) {
+    if !vec![InputState::Mouse, InputState::Touch].contains(&input_state) {
+        return;
+    }
+
+    for (row, interaction) in query.iter() {
+        match interaction {
+            // Clicks cancel the wait for a key
+            Interaction::Clicked if selection.waiting => selection.waiting = false,
+            _ if selection.waiting => {}
+            Interaction::Clicked => {
+                selection.row = row.0;
+                activate(&mut app_state, &mut bindings, &mut options, &mut selection);
+            }
+            Interaction::Hovered => selection.row = row.0,
+            Interaction::None => {}
+        }
+    }
+}
+
+fn input_gamepad(
+    mut app_state: ResMut<State<AppState>>,
+    mut bindings: ResMut<Bindings>,
+    input_state: Res<InputState>,
+    mut options: ResMut<Options>,
+    pad: Res<Pad>,
+    mut selection: ResMut<Selection>,
+) {
+    if !vec![InputState::Gamepad].contains(&input_state) {
+        return;
+    }
+
+    if selection.waiting {
+        if pad.just_pressed(PadButton::East) {
+            selection.waiting = false;
+        }
+
+        return;
+    }
+
+    if pad.just_pressed(PadButton::East) || pad.just_pressed(PadButton::Start) {
+        app_state.set(AppState::Menu).unwrap();
+        return;
+    }
+
+    let rows = Row::all().len();
+    if pad.just_pressed(PadButton::Down) {
+        selection.row = (selection.row + 1) % rows;
+    }
+    if pad.just_pressed(PadButton::Up) {
+        selection.row = (selection.row + rows - 1) % rows;
+    }
+    if pad.just_pressed(PadButton::Left) {
+        Row::all()[selection.row].adjust(&mut options, -1);
+    }
+    if pad.just_pressed(PadButton::Right) {
+        Row::all()[selection.row].adjust(&mut options, 1);
+    }
+    if pad.just_pressed(PadButton::South) {
+        activate(&mut app_state, &mut bindings, &mut options, &mut selection);
+    }
+}
+
+fn input_keyboard(
+    mut app_state: ResMut<State<AppState>>,
+    mut bindings: ResMut<Bindings>,
+    mut input: ResMut<Input<KeyCode>>,
+    input_state: Res<InputState>,
+    mut options: ResMut<Options>,
+    mut selection: ResMut<Selection>,
+) {
+    if !vec![InputState::Keyboard].contains(&input_state) {
+        return;
+    }
+
+    if selection.waiting {
+        if let Some((key, _)) = KEYS.iter().find(|(k, _)| input.just_pressed(*k)) {
+            input.reset(*key);
+            // Keys the action refuses leave the row waiting for another one
+            if let Row::Key(action) = Row::all()[selection.row] {
+                if !bindings.bind(action, *key) {
+                    return;
+                }
+
+                bindings.save();
+            }
+
+            selection.waiting = false;
+        }
+
+        return;
+    }
+
+    if bindings.take(&mut input, Action::Back) {
+        app_state.set(AppState::Menu).unwrap();
+        return;
+    }
+
+    let rows = Row::all().len();
+    if bindings.take(&mut input, Action::Steer(Direction::Down)) {
+        selection.row = (selection.row + 1) % rows;
+    }
+    if bindings.take(&mut input, Action::Steer(Direction::Up)) {
+        selection.row = (selection.row + rows - 1) % rows;
+    }
+    if bindings.take(&mut input, Action::Steer(Direction::Left)) {
+        Row::all()[selection.row].adjust(&mut options, -1);
+    }
+    if bindings.take(&mut input, Action::Steer(Direction::Right)) {
+        Row::all()[selection.row].adjust(&mut options, 1);
+    }
+    if bindings.take(&mut input, Action::Confirm) {
+        activate(&mut app_state, &mut bindings, &mut options, &mut selection);
+    }
+}
+
+fn input_mouse(
+    input: Res<Input<MouseButton>>,
+    input_state: Res<InputState>,
+    mut selection: ResMut<Selection>,
+) {
+    if !vec![InputState::Mouse].contains(&input_state) {
+        return;
+    }
+
+    if selection.waiting && input.just_pressed(MouseButton::Right) {
+        selection.waiting = false;
+    }
+}
+
+fn setup(
+    bindings: Res<Bindings>,
+    mut clear: ResMut<ClearColor>,
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    options: Res<Options>,
+    mut selection: ResMut<Selection>,
+) {
+    *selection = Selection::default();
+    clear.0 = Color::BLACK;
+    commands
+        .spawn_bundle(Camera2dBundle::default())
+        .insert(SettingsComponent)
+        .insert(UiCameraConfig { show_ui: true });
+
+    commands
+        .spawn_bundle(NodeBundle {
+            color: Color::NONE.into(),
+            style: Style {
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::ColumnReverse,
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|p| {
+            p.spawn_bundle(NodeBundle {
+                color: Color::BLACK.into(),
+                style: Style {
+                    justify_content: JustifyContent::Center,
+                    size: Size::new(Val::Percent(100.), Val::Px(TITLE_SIZE)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|p| {
+                p.spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        TITLE_TEXT,
+                        TextStyle {
+                            color: TITLE_COLOR,
+                            font: fonts.bold.clone(),
+                            font_size: TITLE_SIZE,
+                        },
+                    ),
+                    ..Default::default()
+                });
+            });
+
+            for (i, row) in Row::all().iter().enumerate() {
+                p.spawn_bundle(ButtonBundle {
+                    color: Color::NONE.into(),
+                    style: Style {
+                        padding: UiRect::all(Val::Px(ENTRY_PAD)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|p| {
+                    p.spawn_bundle(TextBundle {
+                        text: Text::from_section(
+                            row.label(&bindings, &options, false),
+                            TextStyle {
+                                color: LABEL_COLOR,
+                                font: fonts.regular.clone(),
+                                font_size: ENTRY_SIZE,
+                            },
+                        ),
+                        ..Default::default()
+                    })
+                    .insert(SettingsText(i));
+                })
+                .insert(SettingsRow(i));
+            }
+        })
+        .insert(SettingsComponent);
+}
+
+fn update_rows(
+    bindings: Res<Bindings>,
+    options: Res<Options>,
+    mut query: Query<(&SettingsText, &mut Text)>,
+    selection: Res<Selection>,
+) {
+    let rows = Row::all();
+    for (row, mut text) in query.iter_mut() {
+        let selected = row.0 == selection.row;
+        let color = match selected {
+            true => SELECT_COLOR,
+            false => LABEL_COLOR,
+        };
+
+        let value = rows[row.0].label(&bindings, &options, selected && selection.waiting);
+        if text.sections[0].style.color != color || text.sections[0].value != value {
+            text.sections[0].style.color = color;
+            text.sections[0].value = value;
+        }
+    }
+}
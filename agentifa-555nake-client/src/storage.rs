@@ -0,0 +1,57 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
+
+#[cfg(target_arch = "wasm32")]
+use web_sys::{Storage, UrlSearchParams, XmlHttpRequest};
+
+const PREFIX: &str = "agentifa-555nake";
+
+/// Reads the file at `path` relative to the page URL.
+#[cfg(target_arch = "wasm32")]
+pub fn fetch(path: &str) -> Option<String> {
+    let request = XmlHttpRequest::new().ok()?;
+    request.open_with_async("GET", path, false).ok()?;
+    request.send().ok()?;
+    match request.status().ok()? {
+        200 => request.response_text().ok()?,
+        _ => None,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(key: &str) -> Option<String> {
+    fs::read_to_string(path(key)).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load(key: &str) -> Option<String> {
+    local_storage()?.get_item(&path(key)).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+fn path(key: &str) -> String {
+    format!("{}.{}", PREFIX, key)
+}
+
+/// Reads `key` from the query string of the page URL.
+#[cfg(target_arch = "wasm32")]
+pub fn query(key: &str) -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    UrlSearchParams::new_with_str(&search).ok()?.get(key)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(key: &str, value: &str) {
+    let _ = fs::write(path(key), value);
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(key: &str, value: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.set_item(&path(key), value);
+    }
+}
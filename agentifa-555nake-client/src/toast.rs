@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+use agentifa_555nake_protocol::protocol::Protocol;
+use bevy::{
+    math::Vec3,
+    prelude::{
+        App, BuildChildren, Color, Commands, Component, DespawnRecursiveExt, Entity, EventReader,
+        NodeBundle, Plugin, Query, Res, ResMut, SystemSet, TextBundle, Timer, Transform, With,
+    },
+    text::{Text, TextSection, TextStyle},
+    time::Time,
+    ui::{AlignItems, FlexDirection, JustifyContent, Size, Style, UiRect, Val},
+};
+use bevy_kira_audio::{AudioChannel, AudioControl};
+use naia_bevy_client::{events::MessageEvent, shared::DefaultChannels};
+
+use crate::{AppState, AudioAssets, FontAssets, Sfx};
+
+const TOAST_BG: Color = Color::rgba(0., 0., 0., 0.75);
+const TOAST_DUR: f32 = 3.0; // Seconds
+const TOAST_LBL_ACHIEVEMENT: &str = "Achievement unlocked: ";
+const TOAST_LBL_ANNOUNCE: &str = "Server: ";
+const TOAST_LBL_CLR: Color = Color::CYAN;
+const TOAST_PAD: f32 = 10.0; // Px
+const TOAST_SZE: f32 = 24.0; // Font Size
+const TOAST_TTL_CLR: Color = Color::YELLOW;
+
+pub struct ToastPlugin;
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_exit(AppState::Game).with_system(cleanup))
+            .add_system_set(
+                SystemSet::on_update(AppState::Game)
+                    .with_system(toast_message)
+                    .with_system(update_toasts),
+            )
+            .insert_resource(Toasts {
+                queue: VecDeque::new(),
+                timer: Timer::from_seconds(TOAST_DUR, false),
+            });
+    }
+}
+
+#[derive(Component)]
+struct Toast;
+
+struct Toasts {
+    queue: VecDeque<(&'static str, String)>,
+    timer: Timer,
+}
+
+fn cleanup(
+    mut commands: Commands,
+    query: Query<Entity, With<Toast>>,
+    mut toasts: ResMut<Toasts>,
+) {
+    toasts.queue.clear();
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn toast_message(
+    mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
+    mut toasts: ResMut<Toasts>,
+) {
+    for event in event_reader.iter() {
+        match event {
+            MessageEvent(_, Protocol::AchievementMsg(msg)) => {
+                let title = (*msg.title).clone();
+                toasts.queue.push_back((TOAST_LBL_ACHIEVEMENT, title));
+            }
+            MessageEvent(_, Protocol::AnnounceMsg(msg)) => {
+                let text = (*msg.text).clone();
+                toasts.queue.push_back((TOAST_LBL_ANNOUNCE, text));
+            }
+            _ => (),
+        }
+    }
+}
+
+fn update_toasts(
+    audio: Res<AudioChannel<Sfx>>,
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    query: Query<Entity, With<Toast>>,
+    sounds: Res<AudioAssets>,
+    time: Res<Time>,
+    mut toasts: ResMut<Toasts>,
+) {
+    if !query.is_empty() {
+        if !toasts.timer.tick(time.delta()).finished() {
+            return;
+        }
+
+        for entity in query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    let (label, title) = match toasts.queue.pop_front() {
+        Some(toast) => toast,
+        None => return,
+    };
+
+    let section = |value: &str, color: Color| TextSection {
+        value: value.to_string(),
+        style: TextStyle {
+            color,
+            font: fonts.regular.clone(),
+            font_size: TOAST_SZE,
+        },
+    };
+
+    audio.play(sounds.menu_click_1.clone());
+    toasts.timer.reset();
+    commands
+        .spawn_bundle(NodeBundle {
+            color: Color::NONE.into(),
+            style: Style {
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::FlexStart,
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(3. * Vec3::Z),
+            ..Default::default()
+        })
+        .with_children(|p| {
+            p.spawn_bundle(NodeBundle {
+                color: TOAST_BG.into(),
+                style: Style {
+                    padding: UiRect::all(Val::Px(TOAST_PAD)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|p| {
+                p.spawn_bundle(TextBundle {
+                    text: Text::from_sections(vec![
+                        section(label, TOAST_LBL_CLR),
+                        section(&title, TOAST_TTL_CLR),
+                    ]),
+                    ..Default::default()
+                });
+            });
+        })
+        .insert(Toast);
+}
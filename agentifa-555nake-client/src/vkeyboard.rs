@@ -1,23 +1,28 @@
 use std::fmt::{Display, Formatter};
 
 use bevy::{
-    input::Input,
+    input::{touch::Touches, Input},
     math::Vec2,
     prelude::{
         App, Color, Commands, Component, DespawnRecursiveExt, Entity, EventReader, EventWriter,
-        MouseButton, ParallelSystemDescriptorCoercion, Plugin, Query, Res, SystemSet, Transform,
-        With,
+        MouseButton, ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut, SystemSet,
+        Transform, With,
     },
     sprite::{SpriteSheetBundle, TextureAtlasSprite},
     ui::UiRect,
     window::Windows,
 };
 
-use crate::{AppState, InputState, SpriteSheetAssets};
+use crate::{
+    gamepad::{Pad, PadButton},
+    touch_position, AppState, InputState, SpriteSheetAssets,
+};
 
 const COLOR: Color = Color::rgba(1., 1., 1., 0.5);
+const COLOR_CURSOR: Color = Color::WHITE;
 const COLS: u8 = 10;
 const PAD: f32 = 0.1;
+const ZIDX: f32 = 10.;
 
 #[derive(Clone, Component, Copy)]
 pub struct Button {
@@ -703,22 +708,38 @@ pub struct VKeyboardPlugin;
 impl Plugin for VKeyboardPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<Button>()
-            .add_system_set(SystemSet::on_enter(AppState::Register).with_system(setup))
-            .add_system_set(SystemSet::on_exit(AppState::Register).with_system(cleanup))
-            .add_system_set(
-                SystemSet::on_update(AppState::Register)
-                    .with_system(
-                        input_mouse
-                            .after(InputState::Keyboard)
-                            .after(InputState::Mouse),
-                    )
-                    .with_system(listen_buttons)
-                    .with_system(
-                        update_buttons
-                            .after(InputState::Keyboard)
-                            .after(InputState::Mouse),
-                    ),
-            );
+            .insert_resource(Cursor(Position { x: 0, y: 0 }));
+        for state in [AppState::Register, AppState::Server] {
+            app.add_system_set(SystemSet::on_enter(state).with_system(setup));
+        }
+
+        // The chat shows the keyboard on demand in these states
+        for state in [
+            AppState::Game,
+            AppState::Menu,
+            AppState::Register,
+            AppState::Server,
+        ] {
+            app.add_system_set(SystemSet::on_exit(state.clone()).with_system(cleanup))
+                .add_system_set(
+                    SystemSet::on_update(state)
+                        .with_system(input_gamepad.after(InputState::Gamepad))
+                        .with_system(
+                            input_mouse
+                                .after(InputState::Keyboard)
+                                .after(InputState::Mouse),
+                        )
+                        .with_system(input_touch.after(InputState::Touch))
+                        .with_system(listen_buttons)
+                        .with_system(
+                            update_buttons
+                                .after(InputState::Gamepad)
+                                .after(InputState::Keyboard)
+                                .after(InputState::Mouse)
+                                .after(InputState::Touch),
+                        ),
+                );
+        }
     }
 }
 
@@ -1175,20 +1196,76 @@ impl Modifier {
     }
 }
 
+#[derive(Clone, Copy, PartialEq)]
 struct Position {
     x: usize,
     y: usize,
 }
 
+/// Key selected with the gamepad
+struct Cursor(Position);
+
 #[derive(Component)]
-struct VKeyboardComponent;
+pub struct VKeyboardComponent;
 
 fn cleanup(mut commands: Commands, query: Query<Entity, With<VKeyboardComponent>>) {
+    hide(&mut commands, &query);
+}
+
+pub fn hide(commands: &mut Commands, query: &Query<Entity, With<VKeyboardComponent>>) {
     for entity in query.iter() {
         commands.entity(entity).despawn_recursive();
     }
 }
 
+fn input_gamepad(
+    mut cursor: ResMut<Cursor>,
+    mut event_writer: EventWriter<Button>,
+    input_state: Res<InputState>,
+    pad: Res<Pad>,
+    query: Query<&Button>,
+) {
+    if !vec![InputState::Gamepad].contains(&input_state) {
+        return;
+    }
+
+    if pad.just_pressed(PadButton::South) {
+        if let Some(btn) = query.iter().find(|b| b.position() == cursor.0) {
+            event_writer.send(*btn);
+        }
+    }
+
+    let (dx, dy) = match [
+        PadButton::Down,
+        PadButton::Left,
+        PadButton::Right,
+        PadButton::Up,
+    ]
+    .into_iter()
+    .find(|b| pad.just_pressed(*b))
+    {
+        Some(PadButton::Down) => (0, -1),
+        Some(PadButton::Left) => (-1, 0),
+        Some(PadButton::Right) => (1, 0),
+        Some(PadButton::Up) => (0, 1),
+        _ => return,
+    };
+
+    // Moves to the closest key in the direction, rows differ in length
+    let (x, y) = (cursor.0.x as isize, cursor.0.y as isize);
+    if let Some(pos) = query
+        .iter()
+        .map(|b| b.position())
+        .filter(|p| match dy {
+            0 => p.y as isize == y && (p.x as isize - x).signum() == dx,
+            _ => p.y as isize == y + dy,
+        })
+        .min_by_key(|p| (p.x as isize - x).abs())
+    {
+        cursor.0 = pos;
+    }
+}
+
 fn input_mouse(
     mut event_writer: EventWriter<Button>,
     input: Res<Input<MouseButton>>,
@@ -1225,6 +1302,34 @@ fn input_mouse(
     }
 }
 
+/// Every finger types the key it lands on
+fn input_touch(
+    mut event_writer: EventWriter<Button>,
+    input_state: Res<InputState>,
+    query: Query<(&Button, &Transform)>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+) {
+    if !vec![InputState::Touch].contains(&input_state) {
+        return;
+    }
+
+    let wnd = windows.get_primary().unwrap();
+    for touch in touches.iter_just_pressed() {
+        let pos = touch_position(wnd, touch.position());
+        for (btn, tf) in query.iter() {
+            let offs = 0.5 * tf.scale;
+            if pos.x > tf.translation.x - offs.x
+                && pos.x < tf.translation.x + offs.x
+                && pos.y > tf.translation.y - offs.y
+                && pos.y < tf.translation.y + offs.y
+            {
+                event_writer.send(*btn);
+            }
+        }
+    }
+}
+
 fn listen_buttons(
     mut commands: Commands,
     mut event_reader: EventReader<Button>,
@@ -1268,6 +1373,10 @@ fn setup(/*mut commands: Commands,*/ mut event_writer: EventWriter<Button>) {
     .spawn_bundle(Camera2dBundle::default())
     .insert(VKeyboardComponent);*/
 
+    show(&mut event_writer);
+}
+
+pub fn show(event_writer: &mut EventWriter<Button>) {
     event_writer.send(Button {
         case: Case::Upper,
         key: Key::Letters,
@@ -1276,6 +1385,7 @@ fn setup(/*mut commands: Commands,*/ mut event_writer: EventWriter<Button>) {
 }
 
 fn update_buttons(
+    cursor: Res<Cursor>,
     input_state: Res<InputState>,
     mut query: Query<(&Button, &mut TextureAtlasSprite, &mut Transform)>,
     windows: Res<Windows>,
@@ -1290,13 +1400,15 @@ fn update_buttons(
     let offs_y = 0.5 * height - PAD * blk;
     for (btn, mut tex, mut tf) in query.iter_mut() {
         tex.color = match *input_state {
+            InputState::Gamepad if btn.position() == cursor.0 => COLOR_CURSOR,
+            InputState::Gamepad | InputState::Mouse | InputState::Touch => COLOR,
             InputState::Keyboard => Color::NONE,
-            InputState::Mouse => COLOR,
         };
 
         tf.scale.x = blk_pad;
         tf.scale.y = blk_pad;
         tf.translation.x = btn.position().x as f32 * blk + offs_blk - offs_x;
         tf.translation.y = btn.position().y as f32 * blk + offs_blk - offs_y;
+        tf.translation.z = ZIDX;
     }
 }
@@ -1,51 +1,101 @@
 use naia_shared::Protocolize;
 
+mod achievement_msg;
+mod announce_msg;
 mod assign_msg;
 mod auth;
+mod body;
+mod boost_cmd;
+mod chat_cmd;
+mod chat_msg;
+mod clock;
 mod dir_cmd;
 mod food;
 mod head;
 mod highscore;
 mod highscore_rank;
+mod kick_msg;
 mod name;
+mod ping_cmd;
+mod ping_msg;
 mod position;
 mod quit_cmd;
+mod reject_msg;
+mod resume_cmd;
+mod room;
+mod room_cmd;
+mod rooms_msg;
 mod score;
-mod segment;
 mod start_cmd;
+mod stats_cmd;
+mod stats_msg;
 mod vincible;
 
+pub use achievement_msg::AchievementMsg;
+pub use announce_msg::AnnounceMsg;
 pub use assign_msg::AssignMsg;
 pub use auth::Auth;
+pub use body::Body;
+pub use boost_cmd::BoostCmd;
+pub use chat_cmd::ChatCmd;
+pub use chat_msg::{ChatChannel, ChatMsg};
+pub use clock::Clock;
 pub use dir_cmd::DirCmd;
 pub use food::Food;
 pub use head::{Direction, Head};
 pub use highscore::HighScore;
 pub use highscore_rank::HighScoreRank;
+pub use kick_msg::{KickMsg, KickReason};
 pub use name::Name;
+pub use ping_cmd::PingCmd;
+pub use ping_msg::PingMsg;
 pub use position::Position;
 pub use quit_cmd::QuitCmd;
+pub use reject_msg::{RejectMsg, RejectReason};
+pub use resume_cmd::ResumeCmd;
+pub use room::Room;
+pub use room_cmd::{RoomAction, RoomCmd};
+pub use rooms_msg::RoomsMsg;
 pub use score::Score;
-pub use segment::Segment;
 pub use start_cmd::StartCmd;
+pub use stats_cmd::StatsCmd;
+pub use stats_msg::StatsMsg;
 pub use vincible::Vincible;
 
+pub const CHAT_SIZE: usize = 100;
 pub const GRID_SIZE: usize = 10;
+pub const NAME_SIZE: usize = 30;
 
 #[derive(Protocolize)]
 pub enum Protocol {
+    AchievementMsg(AchievementMsg),
+    AnnounceMsg(AnnounceMsg),
     AssignMsg(AssignMsg),
     Auth(Auth),
+    Body(Body),
+    BoostCmd(BoostCmd),
+    ChatCmd(ChatCmd),
+    ChatMsg(ChatMsg),
+    Clock(Clock),
     DirCmd(DirCmd),
     Food(Food),
     Head(Head),
     HighScore(HighScore),
     HighScoreRank(HighScoreRank),
+    KickMsg(KickMsg),
     Name(Name),
+    PingCmd(PingCmd),
+    PingMsg(PingMsg),
     Position(Position),
     QuitCmd(QuitCmd),
+    RejectMsg(RejectMsg),
+    ResumeCmd(ResumeCmd),
+    Room(Room),
+    RoomCmd(RoomCmd),
+    RoomsMsg(RoomsMsg),
     Score(Score),
-    Segment(Segment),
     StartCmd(StartCmd),
+    StatsCmd(StatsCmd),
+    StatsMsg(StatsMsg),
     Vincible(Vincible),
 }
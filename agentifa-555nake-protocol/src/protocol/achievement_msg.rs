@@ -0,0 +1,14 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct AchievementMsg {
+    pub title: Property<String>,
+}
+
+impl AchievementMsg {
+    pub fn new(title: String) -> Self {
+        AchievementMsg::new_complete(title)
+    }
+}
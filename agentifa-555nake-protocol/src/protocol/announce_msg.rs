@@ -0,0 +1,14 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct AnnounceMsg {
+    pub text: Property<String>,
+}
+
+impl AnnounceMsg {
+    pub fn new(text: String) -> Self {
+        AnnounceMsg::new_complete(text)
+    }
+}
@@ -1,14 +1,16 @@
 use bevy_ecs::prelude::Component;
-use naia_shared::{EntityProperty, Replicate};
+use naia_shared::{EntityProperty, Property, Replicate};
 
 #[derive(Component, Replicate)]
 #[protocol_path = "crate::protocol::Protocol"]
 pub struct AssignMsg {
     pub entity: EntityProperty,
+    /// Lets the client reclaim its snake after a reconnect
+    pub session: Property<String>,
 }
 
 impl AssignMsg {
-    pub fn new() -> Self {
-        AssignMsg::new_complete()
+    pub fn new(session: String) -> Self {
+        AssignMsg::new_complete(session)
     }
 }
@@ -4,11 +4,11 @@ use naia_shared::{Property, Replicate};
 #[derive(Component, Replicate)]
 #[protocol_path = "crate::protocol::Protocol"]
 pub struct Auth {
-    pub key: Property<String>,
+    pub token: Property<String>,
 }
 
 impl Auth {
-    pub fn new(key: &str) -> Self {
-        Auth::new_complete(key.to_string())
+    pub fn new(token: &str) -> Self {
+        Auth::new_complete(token.to_string())
     }
 }
@@ -0,0 +1,18 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+use super::Direction;
+
+/// The segments behind a head. Each entry is the step from the previous cell
+/// to the next segment, starting at the head.
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct Body {
+    pub dirs: Property<Vec<Direction>>,
+}
+
+impl Body {
+    pub fn new() -> Self {
+        Body::new_complete(Vec::new())
+    }
+}
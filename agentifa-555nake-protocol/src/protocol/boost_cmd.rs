@@ -0,0 +1,12 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::Replicate;
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct BoostCmd;
+
+impl BoostCmd {
+    pub fn new() -> Self {
+        BoostCmd::new_complete()
+    }
+}
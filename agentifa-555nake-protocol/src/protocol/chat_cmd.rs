@@ -0,0 +1,19 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+use super::ChatChannel;
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct ChatCmd {
+    pub channel: Property<ChatChannel>,
+    pub name: Property<String>,
+    pub text: Property<String>,
+    pub token: Property<String>,
+}
+
+impl ChatCmd {
+    pub fn new(channel: ChatChannel, name: String, text: String, token: String) -> Self {
+        ChatCmd::new_complete(channel, name, text, token)
+    }
+}
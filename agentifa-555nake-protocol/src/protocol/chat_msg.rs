@@ -0,0 +1,24 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{derive_serde, serde, Property, Replicate};
+
+#[derive(Copy)]
+#[derive_serde]
+pub enum ChatChannel {
+    Global,
+    Room,
+    Server,
+}
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct ChatMsg {
+    pub channel: Property<ChatChannel>,
+    pub name: Property<String>,
+    pub text: Property<String>,
+}
+
+impl ChatMsg {
+    pub fn new(channel: ChatChannel, name: String, text: String) -> Self {
+        ChatMsg::new_complete(channel, name, text)
+    }
+}
@@ -1,14 +1,15 @@
 use bevy_ecs::prelude::Component;
 use naia_shared::{Property, Replicate};
 
+/// Number of simulation ticks since the server started
 #[derive(Component, Replicate)]
 #[protocol_path = "crate::protocol::Protocol"]
-pub struct Segment {
-    pub synced: Property<bool>,
+pub struct Clock {
+    pub tick: Property<u64>,
 }
 
-impl Segment {
+impl Clock {
     pub fn new() -> Self {
-        return Segment::new_complete(false);
+        Clock::new_complete(0)
     }
 }
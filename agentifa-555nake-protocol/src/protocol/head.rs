@@ -46,6 +46,15 @@ impl Direction {
             _ => false,
         }
     }
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::Up => Direction::Down,
+        }
+    }
 }
 
 #[derive(Component, Replicate)]
@@ -53,10 +62,12 @@ impl Direction {
 pub struct Head {
     pub dir: Property<Direction>,
     pub running: Property<bool>,
+    /// Duration of one movement step in milliseconds
+    pub step: Property<u32>,
 }
 
 impl Head {
-    pub fn new() -> Self {
-        return Head::new_complete(Direction::Up, false);
+    pub fn new(step: u32) -> Self {
+        return Head::new_complete(Direction::Up, false, step);
     }
 }
@@ -0,0 +1,26 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{derive_serde, serde, Property, Replicate};
+
+#[derive(Copy)]
+#[derive_serde]
+pub enum KickReason {
+    AuthExpired,
+    AuthInvalid,
+    AuthMalformed,
+    Banned,
+    Kicked,
+    Shutdown,
+    Violations,
+}
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct KickMsg {
+    pub reason: Property<KickReason>,
+}
+
+impl KickMsg {
+    pub fn new(reason: KickReason) -> Self {
+        KickMsg::new_complete(reason)
+    }
+}
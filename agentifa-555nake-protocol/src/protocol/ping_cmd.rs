@@ -0,0 +1,14 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct PingCmd {
+    pub id: Property<u32>,
+}
+
+impl PingCmd {
+    pub fn new(id: u32) -> Self {
+        PingCmd::new_complete(id)
+    }
+}
@@ -0,0 +1,14 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct PingMsg {
+    pub id: Property<u32>,
+}
+
+impl PingMsg {
+    pub fn new(id: u32) -> Self {
+        PingMsg::new_complete(id)
+    }
+}
@@ -4,6 +4,8 @@ use bevy_ecs::prelude::Component;
 use naia_shared::{EntityProperty, Property, Replicate};
 use rand::random;
 
+use super::{Direction, GRID_SIZE};
+
 #[derive(Component, Replicate)]
 #[protocol_path = "crate::protocol::Protocol"]
 pub struct Position {
@@ -26,4 +28,14 @@ impl Position {
     pub fn rnd(grid_size: usize) -> Self {
         Self::new(random::<usize>() % grid_size, random::<usize>() % grid_size)
     }
+
+    /// Moves one cell towards `dir`, wrapping around at the grid borders.
+    pub fn step(&mut self, dir: Direction) {
+        match dir {
+            Direction::Down => *self.y = self.y.checked_sub(1).unwrap_or(GRID_SIZE - 1),
+            Direction::Left => *self.x = self.x.checked_sub(1).unwrap_or(GRID_SIZE - 1),
+            Direction::Right => *self.x = (*self.x + 1) % GRID_SIZE,
+            Direction::Up => *self.y = (*self.y + 1) % GRID_SIZE,
+        }
+    }
 }
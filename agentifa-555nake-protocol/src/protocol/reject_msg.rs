@@ -0,0 +1,28 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{derive_serde, serde, Property, Replicate};
+
+#[derive(Copy)]
+#[derive_serde]
+pub enum RejectReason {
+    NameBlocked,
+    NameEmpty,
+    NameTaken,
+    NameTooLong,
+    RoomCodeInvalid,
+    RoomInvalid,
+    RoomPrivate,
+    RoomStarted,
+    TokenInvalid,
+}
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct RejectMsg {
+    pub reason: Property<RejectReason>,
+}
+
+impl RejectMsg {
+    pub fn new(reason: RejectReason) -> Self {
+        RejectMsg::new_complete(reason)
+    }
+}
@@ -0,0 +1,14 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct ResumeCmd {
+    pub session: Property<String>,
+}
+
+impl ResumeCmd {
+    pub fn new(session: String) -> Self {
+        ResumeCmd::new_complete(session)
+    }
+}
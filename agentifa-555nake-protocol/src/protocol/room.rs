@@ -0,0 +1,31 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+/// State of a private room. The round starts once all `players` are
+/// `ready`, the `owner` may pause it afterwards. Rooms with an invite `code`
+/// are not listed and can only be joined with the code.
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct Room {
+    pub code: Property<String>,
+    pub name: Property<String>,
+    pub owner: Property<String>,
+    pub paused: Property<bool>,
+    pub players: Property<Vec<String>>,
+    pub ready: Property<Vec<String>>,
+    pub started: Property<bool>,
+}
+
+impl Room {
+    pub fn new(name: String, owner: String) -> Self {
+        Room::new_complete(
+            String::new(),
+            name,
+            owner,
+            false,
+            Vec::new(),
+            Vec::new(),
+            false,
+        )
+    }
+}
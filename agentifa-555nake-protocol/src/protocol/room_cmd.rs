@@ -0,0 +1,23 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{derive_serde, serde, Property, Replicate};
+
+#[derive(Copy)]
+#[derive_serde]
+pub enum RoomAction {
+    Lock,
+    Pause,
+    Ready,
+    Resume,
+}
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct RoomCmd {
+    pub action: Property<RoomAction>,
+}
+
+impl RoomCmd {
+    pub fn new(action: RoomAction) -> Self {
+        RoomCmd::new_complete(action)
+    }
+}
@@ -0,0 +1,15 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+/// Private rooms that wait for players
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct RoomsMsg {
+    pub names: Property<Vec<String>>,
+}
+
+impl RoomsMsg {
+    pub fn new(names: Vec<String>) -> Self {
+        RoomsMsg::new_complete(names)
+    }
+}
@@ -5,10 +5,12 @@ use naia_shared::{Property, Replicate};
 #[protocol_path = "crate::protocol::Protocol"]
 pub struct StartCmd {
     pub name: Property<String>,
+    pub room: Property<String>,
+    pub token: Property<String>,
 }
 
 impl StartCmd {
-    pub fn new(name: String) -> Self {
-        StartCmd::new_complete(name)
+    pub fn new(name: String, room: String, token: String) -> Self {
+        StartCmd::new_complete(name, room, token)
     }
 }
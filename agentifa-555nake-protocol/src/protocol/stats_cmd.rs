@@ -0,0 +1,14 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct StatsCmd {
+    pub name: Property<String>,
+}
+
+impl StatsCmd {
+    pub fn new(name: String) -> Self {
+        StatsCmd::new_complete(name)
+    }
+}
@@ -0,0 +1,40 @@
+use bevy_ecs::prelude::Component;
+use naia_shared::{Property, Replicate};
+
+#[derive(Component, Replicate)]
+#[protocol_path = "crate::protocol::Protocol"]
+pub struct StatsMsg {
+    pub name: Property<String>,
+    pub games: Property<usize>,
+    pub food: Property<usize>,
+    pub score: Property<usize>,
+    pub survival: Property<u64>,
+    pub deaths_self: Property<usize>,
+    pub deaths_snake: Property<usize>,
+    pub deaths_quit: Property<usize>,
+}
+
+impl StatsMsg {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: String,
+        games: usize,
+        food: usize,
+        score: usize,
+        survival: u64,
+        deaths_self: usize,
+        deaths_snake: usize,
+        deaths_quit: usize,
+    ) -> Self {
+        StatsMsg::new_complete(
+            name,
+            games,
+            food,
+            score,
+            survival,
+            deaths_self,
+            deaths_snake,
+            deaths_quit,
+        )
+    }
+}
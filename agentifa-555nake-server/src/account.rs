@@ -0,0 +1,125 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+};
+
+use agentifa_555nake_protocol::protocol::RejectReason;
+use bevy::prelude::{Commands, Plugin};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const ACCOUNTS: &str = "accounts.json";
+const TOKEN_SIZE_MIN: usize = 16;
+
+#[derive(Deserialize, Serialize)]
+pub struct Account {
+    #[serde(default)]
+    achievements: Vec<String>,
+    token_hash: String,
+}
+
+pub struct AccountList {
+    entries: HashMap<String, Account>,
+}
+
+impl AccountList {
+    /// Checks `token` against the account reserved for `name`. The first
+    /// valid token used with an unknown name claims that name.
+    pub fn claim(&mut self, name: &str, token: &str) -> Result<(), RejectReason> {
+        if token.len() < TOKEN_SIZE_MIN {
+            return Err(RejectReason::TokenInvalid);
+        }
+
+        let token_hash = hash(token);
+        if let Some(account) = self.entries.get(name) {
+            if account.token_hash != token_hash {
+                return Err(RejectReason::NameTaken);
+            }
+
+            return Ok(());
+        }
+
+        self.entries.insert(
+            name.to_string(),
+            Account {
+                achievements: Vec::new(),
+                token_hash,
+            },
+        );
+
+        self.save();
+        Ok(())
+    }
+
+    fn has_achievement(&self, name: &str, id: &str) -> bool {
+        self.entries
+            .get(name)
+            .map_or(false, |a| a.achievements.iter().any(|i| i == id))
+    }
+
+    fn new() -> Self {
+        let mut entries = HashMap::new();
+        if let Ok(file) = File::open(ACCOUNTS) {
+            let reader = BufReader::new(file);
+            if let Ok(list) = serde_json::from_reader(reader) {
+                entries = list;
+            }
+        }
+
+        AccountList { entries }
+    }
+
+    /// Stores the achievement `id` with the account of `name`. Returns true
+    /// if it was not unlocked before.
+    pub fn unlock(&mut self, name: &str, id: &str) -> bool {
+        if self.has_achievement(name, id) {
+            return false;
+        }
+
+        if let Some(account) = self.entries.get_mut(name) {
+            account.achievements.push(id.to_string());
+            self.save();
+            return true;
+        }
+
+        false
+    }
+
+    fn save(&self) {
+        if let Ok(file) = File::create(ACCOUNTS) {
+            let writer = BufWriter::new(file);
+            let _ = serde_json::to_writer(writer, &self.entries);
+        }
+    }
+
+    /// Checks `token` against the account reserved for `name` without
+    /// claiming unknown names.
+    pub fn verify(&self, name: &str, token: &str) -> Result<(), RejectReason> {
+        match self.entries.get(name) {
+            Some(account) if account.token_hash == hash(token) => Ok(()),
+            Some(_) => Err(RejectReason::NameTaken),
+            None => Err(RejectReason::TokenInvalid),
+        }
+    }
+}
+
+pub struct AccountPlugin;
+
+impl Plugin for AccountPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup);
+    }
+}
+
+/// Hex encoded SHA-256 of `token`, so `ACCOUNTS` never holds a usable token.
+fn hash(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn setup(mut commands: Commands) {
+    commands.insert_resource(AccountList::new());
+}
@@ -0,0 +1,195 @@
+use std::{fs::File, io::BufReader};
+
+use agentifa_555nake_protocol::protocol::{AchievementMsg, Name, Protocol};
+use bevy::prelude::{
+    Commands, EventReader, ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut, Time,
+};
+use chrono::{offset::Local, Duration, NaiveDate};
+use holiday_de::{DateExt, GermanHoliday};
+use naia_bevy_server::{shared::DefaultChannels, Server, UserKey};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    account::AccountList,
+    metrics::Metrics,
+    stats::{update_stats, StatsList},
+    GameEvent, Global, Spawned,
+};
+
+const ACHIEVEMENTS: &str = "achievements.json";
+
+#[derive(Deserialize, Serialize)]
+pub struct Achievement {
+    condition: Condition,
+    id: String,
+    title: String,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// Total food eaten over all games
+    Food(usize),
+    /// Start a game on a holiday theme day
+    Holiday,
+    /// Snake length including the head
+    Length(usize),
+    /// Seconds survived in a single game
+    Survival(u64),
+}
+
+pub struct AchievementList {
+    entries: Vec<Achievement>,
+}
+
+impl AchievementList {
+    fn new() -> Self {
+        let mut entries = Vec::new();
+        if let Ok(file) = File::open(ACHIEVEMENTS) {
+            let reader = BufReader::new(file);
+            if let Ok(list) = serde_json::from_reader(reader) {
+                entries = list;
+            }
+        } else {
+            let mut add = |id: &str, title: &str, condition: Condition| {
+                entries.push(Achievement {
+                    condition,
+                    id: id.to_string(),
+                    title: title.to_string(),
+                });
+            };
+
+            add("food_555", "555 Broccoli", Condition::Food(555));
+            add("holiday", "Feiertagsagent", Condition::Holiday);
+            add("length_20", "Long Pimmler", Condition::Length(20));
+            add("survival_300", "5 Minutes of Fame", Condition::Survival(300));
+        }
+
+        AchievementList { entries }
+    }
+}
+
+pub struct AchievementPlugin;
+
+impl Plugin for AchievementPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup)
+            .add_system(update_achievements.after(update_stats))
+            .add_system(update_survival);
+    }
+}
+
+fn is_holiday(d: NaiveDate) -> bool {
+    (d + Duration::days(1)).is_holiday(GermanHoliday::Allerheiligen)
+        || d.is_holiday(GermanHoliday::Gruendonnerstag)
+        || d.is_holiday(GermanHoliday::Karfreitag)
+        || d.is_holiday(GermanHoliday::Ostermontag)
+        || d.is_holiday(GermanHoliday::Ostersonntag)
+}
+
+fn setup(mut commands: Commands) {
+    commands.insert_resource(AchievementList::new());
+}
+
+fn unlock<'world, 'state>(
+    accounts: &mut AccountList,
+    achievement: &Achievement,
+    metrics: &Metrics,
+    name: &str,
+    server: &mut Server<'world, 'state, Protocol, DefaultChannels>,
+    user_key: &UserKey,
+) {
+    if accounts.unlock(name, &achievement.id) {
+        let msg = AchievementMsg::new(achievement.title.clone());
+        metrics.message_out(&msg);
+        server.send_message(user_key, DefaultChannels::UnorderedReliable, &msg);
+    }
+}
+
+fn update_achievements(
+    mut accounts: ResMut<AccountList>,
+    achievements: Res<AchievementList>,
+    mut event_reader: EventReader<GameEvent>,
+    metrics: Res<Metrics>,
+    mut server: Server<Protocol, DefaultChannels>,
+    stats: Res<StatsList>,
+) {
+    for event in event_reader.iter() {
+        for achievement in achievements.entries.iter() {
+            match (event, achievement.condition) {
+                (GameEvent::Eat { name, user_key, .. }, Condition::Food(food))
+                    if stats.get(name).map_or(false, |s| s.food >= food) =>
+                {
+                    unlock(
+                        &mut accounts,
+                        achievement,
+                        &metrics,
+                        name,
+                        &mut server,
+                        user_key,
+                    );
+                }
+                (
+                    GameEvent::Eat {
+                        length,
+                        name,
+                        user_key,
+                    },
+                    Condition::Length(min),
+                ) if *length >= min => {
+                    unlock(
+                        &mut accounts,
+                        achievement,
+                        &metrics,
+                        name,
+                        &mut server,
+                        user_key,
+                    );
+                }
+                (GameEvent::Start { name, user_key, .. }, Condition::Holiday)
+                    if is_holiday(Local::now().naive_local().date()) =>
+                {
+                    unlock(
+                        &mut accounts,
+                        achievement,
+                        &metrics,
+                        name,
+                        &mut server,
+                        user_key,
+                    );
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+fn update_survival(
+    mut accounts: ResMut<AccountList>,
+    achievements: Res<AchievementList>,
+    global: Res<Global>,
+    metrics: Res<Metrics>,
+    players: Query<(&Name, &Spawned)>,
+    mut server: Server<Protocol, DefaultChannels>,
+    time: Res<Time>,
+) {
+    for (user_key, entity) in global.player_heads.iter() {
+        if let Ok((name, spawned)) = players.get(*entity) {
+            let survival = (time.time_since_startup() - spawned.time).as_secs();
+            for achievement in achievements.entries.iter() {
+                if let Condition::Survival(min) = achievement.condition {
+                    if survival >= min {
+                        unlock(
+                            &mut accounts,
+                            achievement,
+                            &metrics,
+                            &name.text,
+                            &mut server,
+                            user_key,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
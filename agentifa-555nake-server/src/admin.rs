@@ -0,0 +1,290 @@
+use std::{
+    collections::BTreeSet,
+    env,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use agentifa_555nake_protocol::protocol::{AnnounceMsg, KickReason, Name, Protocol, Score};
+use bevy::prelude::{info, warn, Commands, Plugin, Query, Res, ResMut};
+use naia_bevy_server::{shared::DefaultChannels, Server, UserKey};
+
+use crate::{
+    auth::Kicks, highscore::HighScoreList, metrics::Metrics, rules::FOOD_SPAWN_DUR_MAX,
+    session::Sessions, shutdown::Shutdown, Global,
+};
+
+const ADMIN_VAR: &str = "SRV_ADMIN_ADDR";
+const BANS: &str = "bans.json";
+const HELP_TXT: &str = "ban <name> | broadcast <text> | help | highscore remove <name> | \
+highscore reset | highscore set <name> <score> | kick <name> | list | rules food <seconds> | \
+shutdown | unban <name>";
+
+/// A command line read by the admin listener and the channel for its reply
+struct AdminCmd {
+    line: String,
+    reply: Sender<String>,
+}
+
+/// Commands received on the admin listener. It only binds to loopback
+/// addresses given by `SRV_ADMIN_ADDR`, e.g. `127.0.0.1:9556`.
+pub struct Admin {
+    receiver: Mutex<Receiver<AdminCmd>>,
+}
+
+pub struct AdminPlugin;
+
+impl Plugin for AdminPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup).add_system(update_admin);
+    }
+}
+
+/// Names that may not start a game
+pub struct Bans {
+    names: BTreeSet<String>,
+}
+
+impl Bans {
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    fn insert(&mut self, name: &str) -> bool {
+        let inserted = self.names.insert(name.to_string());
+        self.save();
+        inserted
+    }
+
+    fn new() -> Self {
+        let mut names = BTreeSet::new();
+        if let Ok(file) = File::open(BANS) {
+            let reader = BufReader::new(file);
+            if let Ok(list) = serde_json::from_reader(reader) {
+                names = list;
+            }
+        }
+
+        Bans { names }
+    }
+
+    fn remove(&mut self, name: &str) -> bool {
+        let removed = self.names.remove(name);
+        self.save();
+        removed
+    }
+
+    fn save(&self) {
+        if let Ok(file) = File::create(BANS) {
+            let writer = BufWriter::new(file);
+            let _ = serde_json::to_writer(writer, &self.names);
+        }
+    }
+}
+
+fn find_player(global: &Global, name: &str, players: &Query<(&Name, &Score)>) -> Option<UserKey> {
+    global.player_heads.iter().find_map(|(user_key, entity)| {
+        let (player, _) = players.get(*entity).ok()?;
+        (*player.text == name).then_some(*user_key)
+    })
+}
+
+/// Kicks `user_key` and ends its session, so the snake can not be resumed
+pub fn kick<'world, 'state>(
+    kicks: &mut Kicks,
+    metrics: &Metrics,
+    reason: KickReason,
+    server: &mut Server<'world, 'state, Protocol, DefaultChannels>,
+    sessions: &mut Sessions,
+    user_key: &UserKey,
+) {
+    sessions.remove(user_key);
+    kicks.kick(metrics, server, user_key, reason);
+}
+
+fn listen(listener: TcpListener, sender: Sender<AdminCmd>) {
+    for stream in listener.incoming().flatten() {
+        let sender = sender.clone();
+        thread::spawn(move || serve(stream, sender));
+    }
+}
+
+fn serve(stream: TcpStream, sender: Sender<AdminCmd>) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(_) => return,
+    };
+
+    let (reply, replies) = mpsc::channel();
+    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+        let cmd = AdminCmd {
+            line,
+            reply: reply.clone(),
+        };
+
+        if sender.send(cmd).is_err() {
+            return;
+        }
+
+        match replies.recv() {
+            Ok(text) if writeln!(writer, "{}", text).is_ok() => (),
+            _ => return,
+        }
+    }
+}
+
+fn setup(mut commands: Commands) {
+    let (sender, receiver) = mpsc::channel();
+    if let Ok(addr) = env::var(ADMIN_VAR) {
+        match addr.parse::<SocketAddr>() {
+            Ok(addr) if addr.ip().is_loopback() => match TcpListener::bind(addr) {
+                Ok(listener) => {
+                    info!("serving admin console on {}", addr);
+                    thread::spawn(move || listen(listener, sender));
+                }
+                Err(err) => warn!("can not serve admin console on {}: {}", addr, err),
+            },
+            _ => warn!("{} must be a loopback address, got {}", ADMIN_VAR, addr),
+        }
+    }
+
+    commands.insert_resource(Admin {
+        receiver: Mutex::new(receiver),
+    });
+    commands.insert_resource(Bans::new());
+}
+
+fn update_admin(
+    admin: Res<Admin>,
+    mut bans: ResMut<Bans>,
+    mut global: ResMut<Global>,
+    mut highscore: ResMut<HighScoreList>,
+    mut kicks: ResMut<Kicks>,
+    metrics: Res<Metrics>,
+    players: Query<(&Name, &Score)>,
+    mut server: Server<Protocol, DefaultChannels>,
+    mut sessions: ResMut<Sessions>,
+    shutdown: Res<Shutdown>,
+) {
+    let receiver = admin.receiver.lock().unwrap();
+    for cmd in receiver.try_iter() {
+        info!("admin: {}", cmd.line);
+        let line = cmd.line.trim();
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let reply = match (command, args) {
+            ("ban", name) if !name.is_empty() => {
+                if let Some(user_key) = find_player(&global, name, &players) {
+                    let reason = KickReason::Banned;
+                    kick(
+                        &mut kicks,
+                        &metrics,
+                        reason,
+                        &mut server,
+                        &mut sessions,
+                        &user_key,
+                    );
+                }
+
+                match bans.insert(name) {
+                    true => format!("banned {}", name),
+                    false => format!("{} is already banned", name),
+                }
+            }
+            ("broadcast", text) if !text.is_empty() => {
+                let msg = AnnounceMsg::new(text.to_string());
+                for user_key in server.user_keys() {
+                    metrics.message_out(&msg);
+                    server.send_message(&user_key, DefaultChannels::UnorderedReliable, &msg);
+                }
+
+                "sent".to_string()
+            }
+            ("highscore", args) => match args.split_once(' ').unwrap_or((args, "")) {
+                ("remove", name) => match highscore.remove(name) {
+                    true => format!("removed {}", name),
+                    false => format!("{} has no highscore", name),
+                },
+                ("reset", "") => {
+                    highscore.clear();
+                    "highscores reset".to_string()
+                }
+                ("set", args) => match args.rsplit_once(' ').map(|(n, s)| (n, s.parse())) {
+                    Some((name, Ok(score))) => {
+                        highscore.set(name.to_string(), score);
+                        format!("set {} to {}", name, score)
+                    }
+                    _ => HELP_TXT.to_string(),
+                },
+                _ => HELP_TXT.to_string(),
+            },
+            ("kick", name) => match find_player(&global, name, &players) {
+                Some(user_key) => {
+                    let reason = KickReason::Kicked;
+                    kick(
+                        &mut kicks,
+                        &metrics,
+                        reason,
+                        &mut server,
+                        &mut sessions,
+                        &user_key,
+                    );
+
+                    format!("kicked {}", name)
+                }
+                None => format!("{} is not playing", name),
+            },
+            ("list", "") => {
+                let mut list = vec![format!("{} users", server.users_count())];
+                for (user_key, entity) in global.player_heads.iter() {
+                    if let Ok((name, score)) = players.get(*entity) {
+                        let room = global
+                            .user_rooms
+                            .get(user_key)
+                            .and_then(|k| global.rooms.get(k));
+                        let room = room.map_or("", |r| r.name.as_str());
+                        list.push(format!(
+                            "{:?} {} {} {}",
+                            user_key, *name.text, *score.level, room
+                        ));
+                    }
+                }
+
+                list.join("\n")
+            }
+            ("rules", args) => match args.split_once(' ') {
+                Some(("food", secs)) => match secs.parse::<f32>() {
+                    Ok(secs) if secs > 0. && secs <= FOOD_SPAWN_DUR_MAX => {
+                        for room in global.rooms.values_mut() {
+                            room.food_timer.set_duration(Duration::from_secs_f32(secs));
+                        }
+
+                        // Written through, so the next reload of the file keeps it
+                        global.rules.food_spawn_dur = secs;
+                        global.rules.save();
+                        format!("food spawns every {} seconds", secs)
+                    }
+                    _ => HELP_TXT.to_string(),
+                },
+                _ => HELP_TXT.to_string(),
+            },
+            ("shutdown", "") => {
+                shutdown.request();
+                "shutting down".to_string()
+            }
+            ("unban", name) => match bans.remove(name) {
+                true => format!("unbanned {}", name),
+                false => format!("{} is not banned", name),
+            },
+            _ => HELP_TXT.to_string(),
+        };
+
+        let _ = cmd.reply.send(reply);
+    }
+}
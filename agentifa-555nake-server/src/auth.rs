@@ -0,0 +1,226 @@
+use std::{
+    collections::HashMap,
+    env,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use agentifa_555nake_protocol::protocol::{KickMsg, KickReason, Protocol};
+use bevy::prelude::{Commands, EventReader, Plugin, Res, ResMut, Time, Timer};
+use hmac::{Hmac, Mac};
+use naia_bevy_server::{events::AuthorizationEvent, shared::DefaultChannels, Server, UserKey};
+use sha2::Sha256;
+
+use crate::{
+    eventlog::{EventLog, LogEvent},
+    metrics::Metrics,
+    shutdown::Shutdown,
+};
+
+const KICK_DUR: f32 = 1.0;
+const SECRET_VAR: &str = "SRV_SECRET";
+
+#[cfg(debug_assertions)]
+const DEBUG_CLIENT: &str = "debug";
+#[cfg(debug_assertions)]
+const SECRET_DEFAULT: &str = "SRV_SECRET";
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub struct Authorization {
+    secret: String,
+}
+
+impl Authorization {
+    /// Issues a token for `client` that expires after `lifetime`. Tokens have
+    /// the form `<client>.<expiry>.<signature>` with the expiry in seconds
+    /// since the unix epoch and a hex encoded HMAC-SHA256 signature. The
+    /// client id may contain dots.
+    pub fn issue(&self, client: &str, lifetime: Duration) -> String {
+        let expiry = now().checked_add(lifetime).unwrap_or(Duration::MAX);
+        let payload = format!("{}.{}", client, expiry.as_secs());
+        let signature: String = self
+            .mac(&payload)
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        format!("{}.{}", payload, signature)
+    }
+
+    pub fn new() -> Self {
+        let secret = match env::var(SECRET_VAR) {
+            Ok(secret) if !secret.is_empty() => secret,
+            #[cfg(debug_assertions)]
+            _ => SECRET_DEFAULT.to_string(),
+            #[cfg(not(debug_assertions))]
+            _ => panic!("{} is not set", SECRET_VAR),
+        };
+
+        Authorization { secret }
+    }
+
+    /// Checks signature and expiry of `token` and returns the client id. Debug
+    /// builds without `SRV_SECRET` let clients without a token in.
+    pub fn verify(&self, token: &str) -> Result<String, KickReason> {
+        #[cfg(debug_assertions)]
+        if token.is_empty() && self.secret == SECRET_DEFAULT {
+            return Ok(DEBUG_CLIENT.to_string());
+        }
+
+        let (payload, signature) = token.rsplit_once('.').ok_or(KickReason::AuthMalformed)?;
+        let (client, expiry) = payload.rsplit_once('.').ok_or(KickReason::AuthMalformed)?;
+        let expiry: u64 = expiry.parse().map_err(|_| KickReason::AuthMalformed)?;
+        let signature = decode_hex(signature).ok_or(KickReason::AuthMalformed)?;
+
+        self.mac(payload)
+            .verify_slice(&signature)
+            .map_err(|_| KickReason::AuthInvalid)?;
+
+        if expiry < now().as_secs() {
+            return Err(KickReason::AuthExpired);
+        }
+
+        Ok(client.to_string())
+    }
+
+    fn mac(&self, payload: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(self.secret.as_bytes()).unwrap();
+        mac.update(payload.as_bytes());
+        mac
+    }
+}
+
+/// Users that are about to be disconnected. naia can not tell a client why
+/// its connection was rejected, so rejected users are accepted first, told
+/// the reason by a `KickMsg` and disconnected shortly after.
+pub struct Kicks {
+    pending: HashMap<UserKey, KickReason>,
+    timers: HashMap<UserKey, Timer>,
+}
+
+impl Kicks {
+    pub fn contains(&self, user_key: &UserKey) -> bool {
+        self.pending.contains_key(user_key) || self.timers.contains_key(user_key)
+    }
+
+    pub fn kick<'world, 'state>(
+        &mut self,
+        metrics: &Metrics,
+        server: &mut Server<'world, 'state, Protocol, DefaultChannels>,
+        user_key: &UserKey,
+        reason: KickReason,
+    ) {
+        let msg = KickMsg::new(reason);
+        metrics.message_out(&msg);
+        server.send_message(user_key, DefaultChannels::UnorderedReliable, &msg);
+
+        self.timers
+            .insert(*user_key, Timer::from_seconds(KICK_DUR, false));
+    }
+
+    pub fn remove(&mut self, user_key: &UserKey) {
+        self.pending.remove(user_key);
+        self.timers.remove(user_key);
+    }
+
+    pub fn take_pending(&mut self, user_key: &UserKey) -> Option<KickReason> {
+        self.pending.remove(user_key)
+    }
+}
+
+pub struct AuthPlugin;
+
+impl Plugin for AuthPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup).add_system(update_kicks);
+    }
+}
+
+pub fn authorize(
+    authorization: Res<Authorization>,
+    mut event_log: ResMut<EventLog>,
+    mut event_reader: EventReader<AuthorizationEvent<Protocol>>,
+    mut kicks: ResMut<Kicks>,
+    metrics: Res<Metrics>,
+    mut server: Server<Protocol, DefaultChannels>,
+    shutdown: Res<Shutdown>,
+) {
+    for event in event_reader.iter() {
+        if let AuthorizationEvent(user_key, Protocol::Auth(auth_message)) = event {
+            if shutdown.is_active() {
+                kicks.pending.insert(*user_key, KickReason::Shutdown);
+                server.accept_connection(user_key);
+                continue;
+            }
+
+            match authorization.verify(&auth_message.token) {
+                Ok(client) => event_log.write(user_key, LogEvent::AuthAccept { client }),
+                Err(reason) => {
+                    event_log.write(
+                        user_key,
+                        LogEvent::AuthReject {
+                            reason: reason_label(reason),
+                        },
+                    );
+
+                    metrics.auth_rejected(reason);
+                    kicks.pending.insert(*user_key, reason);
+                }
+            }
+
+            server.accept_connection(user_key);
+        }
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn now() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap()
+}
+
+pub fn reason_label(reason: KickReason) -> &'static str {
+    match reason {
+        KickReason::AuthExpired => "expired",
+        KickReason::AuthInvalid => "invalid",
+        KickReason::AuthMalformed => "malformed",
+        KickReason::Banned => "banned",
+        KickReason::Kicked => "kicked",
+        KickReason::Shutdown => "shutdown",
+        KickReason::Violations => "violations",
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.insert_resource(Authorization::new());
+    commands.insert_resource(Kicks {
+        pending: HashMap::new(),
+        timers: HashMap::new(),
+    });
+}
+
+fn update_kicks(
+    mut kicks: ResMut<Kicks>,
+    mut server: Server<Protocol, DefaultChannels>,
+    time: Res<Time>,
+) {
+    kicks.timers.retain(|user_key, timer| {
+        let retain = !timer.tick(time.delta()).finished();
+        if !retain && server.user_exists(user_key) {
+            server.user_mut(user_key).disconnect();
+        }
+
+        retain
+    });
+}
@@ -0,0 +1,120 @@
+use agentifa_555nake_protocol::protocol::{ChatChannel, ChatMsg, Protocol, CHAT_SIZE};
+use bevy::prelude::{Commands, EventReader, Plugin, Res, ResMut, Time};
+use naia_bevy_server::{events::MessageEvent, shared::DefaultChannels, Server};
+
+use crate::{
+    account::AccountList,
+    admin::Bans,
+    auth::Kicks,
+    filter::NameFilter,
+    guard::{Guard, RateLimit},
+    metrics::Metrics,
+    Global,
+};
+
+const CHAT_RATE: usize = 5; // Messages per CHAT_RATE_DUR
+const CHAT_RATE_DUR: f32 = 10.0; // Seconds
+const NOTICE_BLOCKED: &str = "Your message was blocked";
+const NOTICE_NAME: &str = "Register a valid name to chat";
+const NOTICE_RATE: &str = "You are sending messages too fast";
+
+/// Rate limit of chat messages, stricter than the one of `Guard`
+pub struct ChatLimits(RateLimit);
+
+pub struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup).add_system(update_limits);
+    }
+}
+
+pub fn chat_command(
+    accounts: Res<AccountList>,
+    bans: Res<Bans>,
+    mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
+    filter: Res<NameFilter>,
+    global: Res<Global>,
+    guard: Res<Guard>,
+    kicks: Res<Kicks>,
+    mut limits: ResMut<ChatLimits>,
+    metrics: Res<Metrics>,
+    mut server: Server<Protocol, DefaultChannels>,
+    time: Res<Time>,
+) {
+    for event in event_reader.iter() {
+        let (user_key, cmd) = match event {
+            MessageEvent(user_key, _, Protocol::ChatCmd(cmd)) => (user_key, cmd),
+            _ => continue,
+        };
+
+        if guard.ignores(&kicks, user_key) {
+            continue;
+        }
+
+        let mut notice = |text: &str| {
+            let msg = ChatMsg::new(ChatChannel::Server, String::new(), text.to_string());
+            metrics.message_out(&msg);
+            server.send_message(user_key, DefaultChannels::UnorderedReliable, &msg);
+        };
+
+        if !limits.0.allow(time.time_since_startup(), user_key) {
+            notice(NOTICE_RATE);
+            continue;
+        }
+
+        let name = match filter
+            .validate(&cmd.name)
+            .and_then(|name| accounts.verify(&name, &cmd.token).map(|_| name))
+        {
+            Ok(name) if !bans.contains(&name) => name,
+            _ => {
+                notice(NOTICE_NAME);
+                continue;
+            }
+        };
+
+        let text: String = cmd
+            .text
+            .chars()
+            .filter(|c| !c.is_control())
+            .take(CHAT_SIZE)
+            .collect();
+
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        if filter.is_blocked(text) {
+            notice(NOTICE_BLOCKED);
+            continue;
+        }
+
+        let channel = match *cmd.channel {
+            ChatChannel::Room => ChatChannel::Room,
+            _ => ChatChannel::Global,
+        };
+
+        let msg = ChatMsg::new(channel, name, text.to_string());
+        for key in server.user_keys() {
+            if let ChatChannel::Room = channel {
+                if global.user_rooms.get(&key) != global.user_rooms.get(user_key) {
+                    continue;
+                }
+            }
+
+            metrics.message_out(&msg);
+            server.send_message(&key, DefaultChannels::UnorderedReliable, &msg);
+        }
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.insert_resource(ChatLimits(RateLimit::new(CHAT_RATE, CHAT_RATE_DUR)));
+}
+
+/// Drops the chat history of users that left
+fn update_limits(mut limits: ResMut<ChatLimits>, server: Server<Protocol, DefaultChannels>) {
+    limits.0.retain(&server);
+}
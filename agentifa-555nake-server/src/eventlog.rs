@@ -0,0 +1,183 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::Write,
+};
+
+use agentifa_555nake_protocol::protocol::Clock;
+use bevy::prelude::{warn, Commands, EventReader, Plugin, Query, Res, ResMut};
+use chrono::{offset::Local, SecondsFormat};
+use naia_bevy_server::UserKey;
+use serde::Serialize;
+
+use crate::{room::ROOM_MAIN, DeathCause, GameEvent, Global};
+
+const EVENTS: &str = "events.jsonl";
+const EVENTS_KEEP: usize = 5; // Rotated files
+const EVENTS_SIZE: u64 = 10 * 1024 * 1024; // Bytes per file
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case", tag = "event")]
+pub enum LogEvent {
+    AuthAccept {
+        client: String,
+    },
+    AuthReject {
+        reason: &'static str,
+    },
+    Connect,
+    Death {
+        cause: DeathCause,
+        score: usize,
+        survival: f32,
+    },
+    Disconnect,
+    Eat {
+        length: usize,
+    },
+    HighScore {
+        best: bool,
+        score: usize,
+    },
+    Quit,
+    Start,
+    Violation {
+        kind: &'static str,
+    },
+}
+
+#[derive(Serialize)]
+struct Record<'a> {
+    #[serde(flatten)]
+    event: &'a LogEvent,
+    name: Option<&'a str>,
+    room: &'a str,
+    tick: u64,
+    time: String,
+    user: String,
+}
+
+/// Structured game events written as JSON lines to `events.jsonl`. The file
+/// is rotated to `events.jsonl.1` and so on once it exceeds `EVENTS_SIZE`.
+pub struct EventLog {
+    file: Option<File>,
+    names: HashMap<UserKey, String>,
+    rooms: HashMap<UserKey, String>,
+    tick: u64,
+}
+
+impl EventLog {
+    fn new() -> Self {
+        EventLog {
+            file: open(),
+            names: HashMap::new(),
+            rooms: HashMap::new(),
+            tick: 0,
+        }
+    }
+
+    fn rotate(&mut self) {
+        self.file = None;
+        for i in (1..EVENTS_KEEP).rev() {
+            let _ = fs::rename(format!("{}.{}", EVENTS, i), format!("{}.{}", EVENTS, i + 1));
+        }
+
+        let _ = fs::rename(EVENTS, format!("{}.1", EVENTS));
+        self.file = open();
+    }
+
+    pub fn write(&mut self, user_key: &UserKey, event: LogEvent) {
+        let size = self
+            .file
+            .as_ref()
+            .and_then(|f| f.metadata().ok())
+            .map_or(0, |m| m.len());
+
+        if size >= EVENTS_SIZE {
+            self.rotate();
+        }
+
+        let record = Record {
+            event: &event,
+            name: self.names.get(user_key).map(String::as_str),
+            room: self.rooms.get(user_key).map_or(ROOM_MAIN, String::as_str),
+            tick: self.tick,
+            time: Local::now().to_rfc3339_opts(SecondsFormat::Millis, false),
+            user: format!("{:?}", user_key),
+        };
+
+        if let (Some(file), Ok(line)) = (self.file.as_mut(), serde_json::to_string(&record)) {
+            if let Err(err) = writeln!(file, "{}", line) {
+                warn!("can not write event log: {}", err);
+            }
+        }
+
+        if let LogEvent::Death { .. } = event {
+            self.names.remove(user_key);
+            self.rooms.remove(user_key);
+        }
+    }
+}
+
+pub struct EventLogPlugin;
+
+impl Plugin for EventLogPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup)
+            .add_system(update_events)
+            .add_system(update_tick);
+    }
+}
+
+fn open() -> Option<File> {
+    match OpenOptions::new().append(true).create(true).open(EVENTS) {
+        Ok(file) => Some(file),
+        Err(err) => {
+            warn!("can not open event log {}: {}", EVENTS, err);
+            None
+        }
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.insert_resource(EventLog::new());
+}
+
+fn update_events(mut event_log: ResMut<EventLog>, mut event_reader: EventReader<GameEvent>) {
+    for event in event_reader.iter() {
+        match event {
+            GameEvent::Death {
+                cause,
+                score,
+                survival,
+                user_key,
+                ..
+            } => event_log.write(
+                user_key,
+                LogEvent::Death {
+                    cause: *cause,
+                    score: *score,
+                    survival: survival.as_secs_f32(),
+                },
+            ),
+            GameEvent::Eat {
+                length, user_key, ..
+            } => event_log.write(user_key, LogEvent::Eat { length: *length }),
+            GameEvent::Start {
+                name,
+                room,
+                user_key,
+            } => {
+                event_log.names.insert(*user_key, name.clone());
+                event_log.rooms.insert(*user_key, room.clone());
+                event_log.write(user_key, LogEvent::Start);
+            }
+        }
+    }
+}
+
+fn update_tick(clocks: Query<&Clock>, mut event_log: ResMut<EventLog>, global: Res<Global>) {
+    if let Ok(clock) = clocks.get(global.clock) {
+        event_log.tick = *clock.tick;
+    }
+}
@@ -0,0 +1,89 @@
+use std::{fs::File, io::BufReader};
+
+use agentifa_555nake_protocol::protocol::{RejectReason, NAME_SIZE};
+use bevy::prelude::{Commands, Plugin};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+const BLOCKLIST: &str = "blocklist.json";
+
+pub struct NameFilter {
+    blocklist: Vec<String>,
+}
+
+impl NameFilter {
+    /// Returns true if `text` contains a blocked word, even when it is
+    /// disguised by accents, separators or leetspeak.
+    pub fn is_blocked(&self, text: &str) -> bool {
+        let text = fold(text);
+        self.blocklist.iter().any(|w| text.contains(w.as_str()))
+    }
+
+    /// Normalizes `name` and checks it against the length limits and the
+    /// blocklist. Returns the name as it should be shown to other players.
+    pub fn validate(&self, name: &str) -> Result<String, RejectReason> {
+        let name: String = name.nfkc().filter(|c| !c.is_control()).collect();
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(RejectReason::NameEmpty);
+        }
+
+        if name.chars().count() > NAME_SIZE {
+            return Err(RejectReason::NameTooLong);
+        }
+
+        if self.is_blocked(name) {
+            return Err(RejectReason::NameBlocked);
+        }
+
+        Ok(name.to_string())
+    }
+
+    fn new() -> Self {
+        let mut words: Vec<String> = Vec::new();
+        if let Ok(file) = File::open(BLOCKLIST) {
+            let reader = BufReader::new(file);
+            if let Ok(list) = serde_json::from_reader(reader) {
+                words = list;
+            }
+        }
+
+        let blocklist = words
+            .iter()
+            .map(|w| fold(w))
+            .filter(|w| !w.is_empty())
+            .collect();
+
+        NameFilter { blocklist }
+    }
+}
+
+pub struct FilterPlugin;
+
+impl Plugin for FilterPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup);
+    }
+}
+
+fn fold(text: &str) -> String {
+    text.nfkd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            '0' => 'o',
+            '1' | '!' | '|' => 'i',
+            '3' => 'e',
+            '4' | '@' => 'a',
+            '5' | '$' => 's',
+            '7' => 't',
+            '8' => 'b',
+            '9' => 'g',
+            c => c,
+        })
+        .filter(|c| c.is_alphanumeric())
+        .collect()
+}
+
+fn setup(mut commands: Commands) {
+    commands.insert_resource(NameFilter::new());
+}
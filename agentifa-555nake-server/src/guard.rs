@@ -0,0 +1,170 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
+
+use agentifa_555nake_protocol::protocol::{KickReason, Protocol};
+use bevy::prelude::{warn, Commands, EventReader, Plugin, Res, ResMut, Time};
+use naia_bevy_server::{events::MessageEvent, shared::DefaultChannels, Server, UserKey};
+
+use crate::{
+    admin::kick,
+    auth::Kicks,
+    eventlog::{EventLog, LogEvent},
+    metrics::Metrics,
+    session::Sessions,
+};
+
+const MSG_RATE: usize = 40; // Messages per MSG_RATE_DUR
+const MSG_RATE_DUR: f32 = 1.0; // Seconds
+const VIOLATIONS_DUR: f32 = 60.0; // Seconds
+const VIOLATIONS_MAX: usize = 10; // Violations per VIOLATIONS_DUR until a kick
+
+/// Sliding window of the recent message times of each user
+pub struct RateLimit {
+    max: usize,
+    sent: HashMap<UserKey, VecDeque<Duration>>,
+    window: Duration,
+}
+
+impl RateLimit {
+    /// Records a message of `user_key` at `now`. Returns false if the user
+    /// already sent `max` messages within the window.
+    pub fn allow(&mut self, now: Duration, user_key: &UserKey) -> bool {
+        let sent = self.sent.entry(*user_key).or_default();
+        while sent.front().map_or(false, |t| now - *t > self.window) {
+            sent.pop_front();
+        }
+
+        if sent.len() >= self.max {
+            return false;
+        }
+
+        sent.push_back(now);
+        true
+    }
+
+    pub fn new(max: usize, window: f32) -> Self {
+        RateLimit {
+            max,
+            sent: HashMap::new(),
+            window: Duration::from_secs_f32(window),
+        }
+    }
+
+    /// Drops the history of users that left
+    pub fn retain(&mut self, server: &Server<Protocol, DefaultChannels>) {
+        self.sent.retain(|user_key, _| server.user_exists(user_key));
+    }
+}
+
+/// Protects the server against misbehaving clients. Every user may send at
+/// most `MSG_RATE` messages per `MSG_RATE_DUR`. Dropped or invalid messages
+/// count as violations and `VIOLATIONS_MAX` of them within `VIOLATIONS_DUR`
+/// get the user kicked.
+pub struct Guard {
+    limited: HashSet<UserKey>,
+    messages: RateLimit,
+    reports: Vec<(UserKey, Violation)>,
+    violations: RateLimit,
+}
+
+impl Guard {
+    /// Returns true if the messages of `user_key` are to be dropped, because
+    /// the user is kicked or exceeded the rate limit.
+    pub fn ignores(&self, kicks: &Kicks, user_key: &UserKey) -> bool {
+        kicks.contains(user_key) || self.limited.contains(user_key)
+    }
+
+    /// Queues a violation to be logged and counted by `update_guard`
+    pub fn report(&mut self, user_key: &UserKey, violation: Violation) {
+        self.reports.push((*user_key, violation));
+    }
+}
+
+pub struct GuardPlugin;
+
+impl Plugin for GuardPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup).add_system(update_guard);
+    }
+}
+
+/// Checks the rate limit before any handler sees a message. Users above the
+/// limit are ignored by every handler for the rest of the batch.
+pub fn filter_messages(
+    mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
+    mut guard: ResMut<Guard>,
+    kicks: Res<Kicks>,
+    time: Res<Time>,
+) {
+    guard.limited.clear();
+    for event in event_reader.iter() {
+        let MessageEvent(user_key, _, _) = event;
+        if kicks.contains(user_key) {
+            continue;
+        }
+
+        if !guard.messages.allow(time.time_since_startup(), user_key) {
+            guard.limited.insert(*user_key);
+            guard.report(user_key, Violation::RateExceeded);
+        }
+    }
+}
+
+pub enum Violation {
+    BoostWithoutHead,
+    DirWithoutHead,
+    RateExceeded,
+    RoomCodeInvalid,
+    SecondSnake,
+}
+
+fn setup(mut commands: Commands) {
+    commands.insert_resource(Guard {
+        limited: HashSet::new(),
+        messages: RateLimit::new(MSG_RATE, MSG_RATE_DUR),
+        reports: Vec::new(),
+        violations: RateLimit::new(VIOLATIONS_MAX, VIOLATIONS_DUR),
+    });
+}
+
+fn update_guard(
+    mut event_log: ResMut<EventLog>,
+    mut guard: ResMut<Guard>,
+    mut kicks: ResMut<Kicks>,
+    metrics: Res<Metrics>,
+    mut server: Server<Protocol, DefaultChannels>,
+    mut sessions: ResMut<Sessions>,
+    time: Res<Time>,
+) {
+    let guard = &mut *guard;
+    for (user_key, violation) in guard.reports.drain(..) {
+        let kind = match violation {
+            Violation::BoostWithoutHead => "boost_without_head",
+            Violation::DirWithoutHead => "dir_without_head",
+            Violation::RateExceeded => "rate_exceeded",
+            Violation::RoomCodeInvalid => "room_code_invalid",
+            Violation::SecondSnake => "second_snake",
+        };
+
+        warn!("violation of {:?}: {}", user_key, kind);
+        event_log.write(&user_key, LogEvent::Violation { kind });
+
+        let now = time.time_since_startup();
+        if !guard.violations.allow(now, &user_key) && !kicks.contains(&user_key) {
+            let reason = KickReason::Violations;
+            kick(
+                &mut kicks,
+                &metrics,
+                reason,
+                &mut server,
+                &mut sessions,
+                &user_key,
+            );
+        }
+    }
+
+    guard.messages.retain(&server);
+    guard.violations.retain(&server);
+}
@@ -1,13 +1,13 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs::File,
     io::{BufReader, BufWriter},
 };
 
 use agentifa_555nake_protocol::protocol::{HighScore, HighScoreRank, Protocol};
-use bevy::prelude::{Commands, DetectChanges, Entity, Plugin, Query, Res, ResMut};
+use bevy::prelude::{Commands, DetectChanges, Entity, Local, Plugin, Query, Res, ResMut};
 use frank::rank_dense_greater;
-use naia_bevy_server::{shared::DefaultChannels, Server};
+use naia_bevy_server::{shared::DefaultChannels, RoomKey, Server};
 
 use crate::Global;
 
@@ -20,30 +20,21 @@ pub struct HighScoreList {
 }
 
 impl HighScoreList {
-    pub fn insert(&mut self, name: String, score: usize) {
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.update();
+    }
+
+    /// Records `score` for `name` and returns true if it is a new best.
+    pub fn insert(&mut self, name: String, score: usize) -> bool {
+        let best = self.entries.get(&name).map_or(true, |s| score > *s);
         self.entries
             .entry(name)
             .and_modify(|s| *s = (*s).max(score))
             .or_insert(score);
 
-        self.ranks.clear();
-        for (name, score) in self.entries.iter() {
-            self.ranks.push((name.clone(), *score));
-        }
-
-        self.ranks.sort_by_key(|(_, s)| *s);
-        self.ranks.reverse();
-
-        let ranks: Vec<usize> = self.ranks.iter().map(|(_, s)| *s).collect();
-        let ranks = rank_dense_greater(&ranks);
-        for (i, (_, r)) in self.ranks.iter_mut().enumerate() {
-            *r = ranks[i];
-        }
-
-        if let Ok(file) = File::create(HIGHSCORE) {
-            let writer = BufWriter::new(file);
-            let _ = serde_json::to_writer(writer, &self.entries);
-        }
+        self.update();
+        best
     }
 
     fn new() -> Self {
@@ -70,6 +61,40 @@ impl HighScoreList {
 
         highscore
     }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let removed = self.entries.remove(name).is_some();
+        self.update();
+        removed
+    }
+
+    /// Overwrites the score of `name`, even with a lower one.
+    pub fn set(&mut self, name: String, score: usize) {
+        self.entries.insert(name, score);
+        self.update();
+    }
+
+    /// Ranks the entries and writes them to disk.
+    fn update(&mut self) {
+        self.ranks.clear();
+        for (name, score) in self.entries.iter() {
+            self.ranks.push((name.clone(), *score));
+        }
+
+        self.ranks.sort_by_key(|(_, s)| *s);
+        self.ranks.reverse();
+
+        let ranks: Vec<usize> = self.ranks.iter().map(|(_, s)| *s).collect();
+        let ranks = rank_dense_greater(&ranks);
+        for (i, (_, r)) in self.ranks.iter_mut().enumerate() {
+            *r = ranks[i];
+        }
+
+        if let Ok(file) = File::create(HIGHSCORE) {
+            let writer = BufWriter::new(file);
+            let _ = serde_json::to_writer(writer, &self.entries);
+        }
+    }
 }
 
 pub struct HighScorePlugin;
@@ -90,13 +115,33 @@ fn update_highscore(
     global: Res<Global>,
     mut list: ResMut<HighScoreList>,
     mut query: Query<&mut HighScore>,
+    mut rooms: Local<HashSet<RoomKey>>,
     mut server: Server<Protocol, DefaultChannels>,
 ) {
+    // Every room shows the highscores
+    rooms.retain(|room_key| global.rooms.contains_key(room_key));
+    for room_key in global.rooms.keys() {
+        if rooms.insert(*room_key) {
+            for entity in list.entities.values() {
+                server.room_mut(room_key).add_entity(entity);
+            }
+        }
+    }
+
     if !list.is_changed() {
         return;
     }
 
     let list = &mut *list;
+    list.entities.retain(|name, entity| {
+        let retain = list.entries.contains_key(name);
+        if !retain {
+            server.entity_mut(entity).despawn();
+        }
+
+        retain
+    });
+
     for (name, score) in list.entries.iter() {
         if let Some(entity) = list.entities.get(name) {
             let mut hs = query.get_mut(*entity).unwrap();
@@ -104,14 +149,13 @@ fn update_highscore(
                 *hs.score = *score;
             }
         } else {
-            list.entities.insert(
-                name.clone(),
-                server
-                    .spawn()
-                    .enter_room(&global.main_room_key)
-                    .insert(HighScore::new(name.clone(), *score))
-                    .id(),
-            );
+            let mut entity = server.spawn();
+            for room_key in global.rooms.keys() {
+                entity.enter_room(room_key);
+            }
+
+            let entity = entity.insert(HighScore::new(name.clone(), *score)).id();
+            list.entities.insert(name.clone(), entity);
         }
     }
 }
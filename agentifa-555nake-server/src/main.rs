@@ -1,35 +1,69 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    time::{Duration, Instant},
+};
 
+use account::{AccountList, AccountPlugin};
+use achievement::AchievementPlugin;
+use admin::{AdminPlugin, Bans};
 use agentifa_555nake_protocol::protocol::{
-    AssignMsg, Direction, Food, Head, Name, Position, Protocol, QuitCmd, Score, Segment, Vincible,
-    GRID_SIZE,
+    AssignMsg, Body, Clock, Food, Head, KickReason, Name, PingMsg, Position, Protocol, QuitCmd,
+    RejectMsg, RejectReason, Score, Vincible, GRID_SIZE,
 };
+use auth::{authorize, AuthPlugin, Authorization, Kicks};
 use bevy::{
     log::LogPlugin,
     prelude::{
-        App, Commands, Component, Entity, EventReader, ParallelSystemDescriptorCoercion, Query,
-        Res, ResMut, Time, Timer, With, Without,
+        App, Commands, Component, Entity, EventReader, EventWriter, Local,
+        ParallelSystemDescriptorCoercion, Query, Res, ResMut, Time, Timer, With, Without,
     },
     MinimalPlugins,
 };
+use chat::{chat_command, ChatPlugin};
+use eventlog::{EventLog, EventLogPlugin, LogEvent};
+use filter::{FilterPlugin, NameFilter};
+use guard::{filter_messages, Guard, GuardPlugin, Violation};
 use highscore::{HighScoreList, HighScorePlugin};
+use metrics::{tick_end, tick_start, Metrics, MetricsPlugin};
 use naia_bevy_server::{
-    events::{AuthorizationEvent, ConnectionEvent, DisconnectionEvent, MessageEvent},
+    events::{ConnectionEvent, DisconnectionEvent, MessageEvent},
     shared::{DefaultChannels, SharedConfig},
     Plugin as ServerPlugin, RoomKey, Server, ServerAddrs, ServerConfig, Stage, UserKey,
 };
-use obfstr::obfstr;
-
+use room::{room_command, GameRoom, RoomPlugin};
+use rules::{Rules, RulesPlugin};
+use serde::{Deserialize, Serialize};
+use session::{SessionPlugin, Sessions};
+use shutdown::ShutdownPlugin;
+use stats::{stats_command, StatsPlugin};
+
+mod account;
+mod achievement;
+mod admin;
+mod auth;
+mod chat;
+mod eventlog;
+mod filter;
+mod guard;
 mod highscore;
+mod metrics;
+mod room;
+mod rules;
+mod session;
+mod shutdown;
+mod stats;
 
-const FOOD_SPAWN_DUR: f32 = 10.0;
-const HEAD_MOV_DUR_START: f32 = 0.5;
-const HEAD_MOV_DUR_FAKTOR: f32 = 0.95;
 const SRV_ADDR: &str = "127.0.0.1";
 const SRV_PORT: &str = "55500";
 const SRV_PORT_WRTC: &str = "55501";
 const STARTPOS_X: usize = 5;
 const STARTPOS_Y: usize = 5;
+const TICK_RATE: u64 = 20; // Ticks per second
+const TICK_RATE_VAR: &str = "SRV_TICK_RATE";
+const TOKEN_CMD: &str = "token";
+const TOKEN_LIFETIME: u64 = 24; // Hours
+const TOKEN_USAGE: &str = "usage: token <client> [hours]";
 
 #[cfg(debug_assertions)]
 const SRV_ADDR_PUB: &str = SRV_ADDR;
@@ -41,82 +75,326 @@ const SRV_PROT: &str = "http";
 #[cfg(not(debug_assertions))]
 const SRV_PROT: &str = env!("SRV_PROT");
 
-#[cfg(not(debug_assertions))]
-const SRV_KEY: &str = env!("SRV_KEY");
-#[cfg(debug_assertions)]
-const SRV_KEY: &str = "SRV_KEY";
+#[derive(Clone, Copy, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum DeathCause {
+    Quit,
+    SelfCollision,
+    SnakeCollision,
+}
+
+pub enum GameEvent {
+    Death {
+        cause: DeathCause,
+        name: String,
+        score: usize,
+        survival: Duration,
+        user_key: UserKey,
+    },
+    Eat {
+        length: usize,
+        name: String,
+        user_key: UserKey,
+    },
+    Start {
+        name: String,
+        room: String,
+        user_key: UserKey,
+    },
+}
 
 struct Global {
-    food_timer: Timer,
-    last_time: Duration,
+    clock: Entity,
     main_room_key: RoomKey,
     player_heads: HashMap<UserKey, Entity>,
     player_keys: HashMap<Entity, UserKey>,
     player_segments: HashMap<Entity, UserKey>,
+    rooms: HashMap<RoomKey, GameRoom>,
+    rules: Rules,
     segment_order: Vec<Entity>,
+    tick_dur: Duration,
+    user_rooms: HashMap<UserKey, RoomKey>,
 }
 
+/// Room of a head, segment or food. Only things in the same room collide.
 #[derive(Component)]
-struct TimerComponent {
-    timer: Timer,
+struct InRoom(RoomKey);
+
+/// Segments only live on the server, clients rebuild them from the `Body`
+/// of the head.
+#[derive(Component)]
+struct Segment;
+
+#[derive(Component)]
+struct Spawned {
+    time: Duration,
 }
 
-fn authorize(
-    mut event_reader: EventReader<AuthorizationEvent<Protocol>>,
-    mut server: Server<Protocol, DefaultChannels>,
+/// Moves a snake one step whenever `acc` reaches a full step. `speed` is
+/// given in steps per tick and includes a running `boost`.
+#[derive(Component)]
+struct Stepper {
+    acc: f32,
+    boost: Option<Timer>,
+    speed: f32,
+}
+
+/// Speeds a snake up for the cost of its last segments, if the rules allow
+/// boosts and it is not boosted already.
+fn boost_command(
+    mut commands: Commands,
+    mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
+    mut global: ResMut<Global>,
+    mut guard: ResMut<Guard>,
+    mut heads: Query<(&mut Body, &mut Head, &InRoom, &mut Score, &mut Stepper)>,
+    kicks: Res<Kicks>,
 ) {
+    let global = &mut *global;
     for event in event_reader.iter() {
-        if let AuthorizationEvent(user_key, Protocol::Auth(auth_message)) = event {
-            let key = &*auth_message.key;
-            if key == obfstr!(SRV_KEY) {
-                server.accept_connection(&user_key);
-            } else {
-                server.reject_connection(&user_key);
+        let user_key = match event {
+            MessageEvent(user_key, _, Protocol::BoostCmd(_)) => user_key,
+            _ => continue,
+        };
+
+        if guard.ignores(&kicks, user_key) {
+            continue;
+        }
+
+        let entity = match global.player_heads.get(user_key) {
+            Some(entity) => *entity,
+            None => {
+                guard.report(user_key, Violation::BoostWithoutHead);
+                continue;
             }
+        };
+
+        let (cost, dur) = match &global.rules.boost {
+            Some(boost) => (boost.cost, boost.dur),
+            None => continue,
+        };
+
+        let (mut body, mut head, in_room, mut score, mut stepper) = match heads.get_mut(entity) {
+            Ok(head) => head,
+            Err(_) => continue,
+        };
+
+        let running = global
+            .rooms
+            .get(&in_room.0)
+            .map_or(false, GameRoom::is_running);
+        if !running || stepper.boost.is_some() || *score.level < cost {
+            continue;
         }
+
+        let tail: Vec<Entity> = global
+            .segment_order
+            .iter()
+            .rev()
+            .filter(|e| global.player_segments.get(*e) == Some(user_key))
+            .take(cost)
+            .copied()
+            .collect();
+
+        for entity in tail {
+            body.dirs.pop();
+            commands.entity(entity).despawn();
+            global.player_segments.remove(&entity);
+            global.segment_order.retain(|e| *e != entity);
+        }
+
+        *score.level -= cost;
+        stepper.boost = Some(Timer::from_seconds(dur, false));
+        set_speed(global, &mut head, *score.level, &mut stepper);
     }
 }
 
 fn command_message<'world, 'state>(
+    mut accounts: ResMut<AccountList>,
+    bans: Res<Bans>,
     mut commands: Commands,
+    mut event_log: ResMut<EventLog>,
     mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
+    mut event_writer: EventWriter<GameEvent>,
+    filter: Res<NameFilter>,
     mut global: ResMut<Global>,
-    mut query: Query<&mut Head>,
+    mut guard: ResMut<Guard>,
+    mut kicks: ResMut<Kicks>,
+    metrics: Res<Metrics>,
+    players: Query<(&Name, &Score, &Spawned)>,
+    mut query: Query<(&mut Head, &InRoom)>,
     mut server: Server<'world, 'state, Protocol, DefaultChannels>,
+    mut sessions: ResMut<Sessions>,
+    time: Res<Time>,
 ) {
     for event in event_reader.iter() {
+        let MessageEvent(user_key, _, message) = event;
+        if guard.ignores(&kicks, user_key) {
+            continue;
+        }
+
+        metrics.message_in(message);
+
         match event {
             MessageEvent(user_key, _, Protocol::DirCmd(msg)) => {
-                if let Some(entity) = global.player_heads.get(user_key) {
-                    if let Ok(mut head) = query.get_mut(*entity) {
-                        *head.dir = *msg.dir;
-                        *head.running = true;
+                match global.player_heads.get(user_key) {
+                    Some(entity) => {
+                        if let Ok((mut head, _)) = query.get_mut(*entity) {
+                            *head.dir = *msg.dir;
+                            *head.running = true;
+                        }
                     }
+                    None => guard.report(user_key, Violation::DirWithoutHead),
                 }
             }
+            MessageEvent(user_key, _, Protocol::PingCmd(msg)) => {
+                let ping_msg = PingMsg::new(*msg.id);
+                metrics.message_out(&ping_msg);
+                server.send_message(user_key, DefaultChannels::UnorderedUnreliable, &ping_msg);
+            }
             MessageEvent(user_key, _, Protocol::QuitCmd(_)) => {
-                despawn_player(&mut global, &mut server, user_key);
+                event_log.write(user_key, LogEvent::Quit);
+                if let Some(event) =
+                    death_event(&global, &players, &time, user_key, DeathCause::Quit)
+                {
+                    event_writer.send(event);
+                }
+
+                sessions.remove(user_key);
+                despawn_player(&mut commands, &mut global, &mut server, user_key);
+            }
+            MessageEvent(user_key, _, Protocol::ResumeCmd(msg)) => {
+                if global.player_heads.contains_key(user_key) {
+                    guard.report(user_key, Violation::SecondSnake);
+                    continue;
+                }
+
+                let (entity, old_key) = match sessions.resume(&msg.session, user_key) {
+                    Some(orphan) => orphan,
+                    None => {
+                        let quit_cmd = QuitCmd::new();
+                        metrics.message_out(&quit_cmd);
+                        server.send_message(
+                            user_key,
+                            DefaultChannels::UnorderedReliable,
+                            &quit_cmd,
+                        );
+
+                        continue;
+                    }
+                };
+
+                if let (Ok((_, in_room)), Ok((name, _, _))) =
+                    (query.get(entity), players.get(entity))
+                {
+                    room::enter(&mut global, &mut server, user_key, in_room.0, &name.text);
+                }
+
+                global.player_heads.insert(*user_key, entity);
+                global.player_keys.insert(entity, *user_key);
+                for key in global.player_segments.values_mut() {
+                    if *key == old_key {
+                        *key = *user_key;
+                    }
+                }
+
+                let mut assign_msg = AssignMsg::new((*msg.session).clone());
+                assign_msg.entity.set(&server, &entity);
+                metrics.message_out(&assign_msg);
+                server.send_message(user_key, DefaultChannels::UnorderedReliable, &assign_msg);
             }
             MessageEvent(user_key, _, Protocol::StartCmd(msg)) => {
+                // One snake per user, a second head would orphan the first
+                if global.player_heads.contains_key(user_key) {
+                    guard.report(user_key, Violation::SecondSnake);
+                    continue;
+                }
+
+                let name = match filter
+                    .validate(&msg.name)
+                    .and_then(|name| accounts.claim(&name, &msg.token).map(|_| name))
+                {
+                    Ok(name) => name,
+                    Err(reason) => {
+                        let reject_msg = RejectMsg::new(reason);
+                        metrics.message_out(&reject_msg);
+                        server.send_message(
+                            user_key,
+                            DefaultChannels::UnorderedReliable,
+                            &reject_msg,
+                        );
+
+                        continue;
+                    }
+                };
+
+                if bans.contains(&name) {
+                    kicks.kick(&metrics, &mut server, user_key, KickReason::Banned);
+                    continue;
+                }
+
+                let room_key = match room::join(
+                    &filter,
+                    &mut global,
+                    &mut server,
+                    user_key,
+                    &name,
+                    &msg.room,
+                ) {
+                    Ok(room_key) => room_key,
+                    Err(reason) => {
+                        if matches!(reason, RejectReason::RoomCodeInvalid) {
+                            guard.report(user_key, Violation::RoomCodeInvalid);
+                        }
+
+                        let reject_msg = RejectMsg::new(reason);
+                        metrics.message_out(&reject_msg);
+                        server.send_message(
+                            user_key,
+                            DefaultChannels::UnorderedReliable,
+                            &reject_msg,
+                        );
+
+                        continue;
+                    }
+                };
+
+                let mut head = Head::new(0);
+                let mut stepper = Stepper {
+                    acc: 0.,
+                    boost: None,
+                    speed: 0.,
+                };
+
+                set_speed(&global, &mut head, 0, &mut stepper);
                 let entity = server
                     .spawn()
-                    .enter_room(&global.main_room_key)
-                    .insert(Head::new())
-                    .insert(Name::new((*msg.name).clone()))
+                    .enter_room(&room_key)
+                    .insert(Body::new())
+                    .insert(head)
+                    .insert(Name::new(name.clone()))
                     .insert(Position::new(STARTPOS_X, STARTPOS_Y))
                     .insert(Score::new())
                     .id();
 
-                commands.entity(entity).insert(TimerComponent {
-                    timer: Timer::from_seconds(HEAD_MOV_DUR_START, true),
-                });
+                commands
+                    .entity(entity)
+                    .insert(InRoom(room_key))
+                    .insert(Spawned {
+                        time: time.time_since_startup(),
+                    })
+                    .insert(stepper);
 
                 global.player_heads.insert(*user_key, entity);
                 global.player_keys.insert(entity, *user_key);
 
-                let mut assign_msg = AssignMsg::new();
+                let mut assign_msg = AssignMsg::new(sessions.issue(user_key));
                 assign_msg.entity.set(&server, &entity);
+                metrics.message_out(&assign_msg);
                 server.send_message(user_key, DefaultChannels::UnorderedReliable, &assign_msg);
+                event_writer.send(GameEvent::Start {
+                    name,
+                    room: global.rooms[&room_key].name.clone(),
+                    user_key: *user_key,
+                });
             }
             _ => (),
         }
@@ -124,17 +402,49 @@ fn command_message<'world, 'state>(
 }
 
 fn connect<'world, 'state>(
-    global: Res<Global>,
+    mut global: ResMut<Global>,
+    mut event_log: ResMut<EventLog>,
     mut event_reader: EventReader<ConnectionEvent>,
+    mut kicks: ResMut<Kicks>,
+    metrics: Res<Metrics>,
     mut server: Server<'world, 'state, Protocol, DefaultChannels>,
 ) {
     for event in event_reader.iter() {
         let ConnectionEvent(user_key) = event;
+        event_log.write(user_key, LogEvent::Connect);
+        if let Some(reason) = kicks.take_pending(user_key) {
+            kicks.kick(&metrics, &mut server, user_key, reason);
+            continue;
+        }
+
+        metrics.connected();
+
         server.user_mut(&user_key).enter_room(&global.main_room_key);
+        let main_room_key = global.main_room_key;
+        global.user_rooms.insert(*user_key, main_room_key);
     }
 }
 
+fn death_event(
+    global: &Global,
+    players: &Query<(&Name, &Score, &Spawned)>,
+    time: &Time,
+    user_key: &UserKey,
+    cause: DeathCause,
+) -> Option<GameEvent> {
+    let entity = global.player_heads.get(user_key)?;
+    let (name, score, spawned) = players.get(*entity).ok()?;
+    Some(GameEvent::Death {
+        cause,
+        name: (*name.text).clone(),
+        score: *score.level,
+        survival: time.time_since_startup() - spawned.time,
+        user_key: *user_key,
+    })
+}
+
 fn despawn_player<'world, 'state>(
+    commands: &mut Commands,
     global: &mut Global,
     server: &mut Server<'world, 'state, Protocol, DefaultChannels>,
     user_key: &UserKey,
@@ -148,7 +458,7 @@ fn despawn_player<'world, 'state>(
         let retain = *v != *user_key;
         if !retain {
             global.segment_order.retain(|e| *e != *k);
-            server.entity_mut(k).despawn();
+            commands.entity(*k).despawn();
         }
 
         retain
@@ -156,64 +466,195 @@ fn despawn_player<'world, 'state>(
 }
 
 fn disconnect<'world, 'state>(
+    mut commands: Commands,
+    mut event_log: ResMut<EventLog>,
     mut event_reader: EventReader<DisconnectionEvent>,
+    mut event_writer: EventWriter<GameEvent>,
     mut global: ResMut<Global>,
+    mut heads: Query<&mut Head>,
+    mut kicks: ResMut<Kicks>,
+    metrics: Res<Metrics>,
+    players: Query<(&Name, &Score, &Spawned)>,
     mut server: Server<'world, 'state, Protocol, DefaultChannels>,
+    mut sessions: ResMut<Sessions>,
+    time: Res<Time>,
 ) {
     for event in event_reader.iter() {
         let DisconnectionEvent(user_key, _) = event;
-        despawn_player(&mut global, &mut server, user_key);
+        kicks.remove(user_key);
+        event_log.write(user_key, LogEvent::Disconnect);
+        if global.user_rooms.contains_key(user_key) {
+            metrics.disconnected();
+        }
+
+        room::leave(&mut global, &mut server, user_key);
+        if let Some(entity) = global.player_heads.get(user_key).copied() {
+            if sessions.orphan(user_key, entity) {
+                global.player_heads.remove(user_key);
+                global.player_keys.remove(&entity);
+                if let Ok(mut head) = heads.get_mut(entity) {
+                    *head.running = false;
+                }
+
+                continue;
+            }
+        }
+
+        if let Some(event) = death_event(&global, &players, &time, user_key, DeathCause::Quit) {
+            event_writer.send(event);
+        }
+
+        despawn_player(&mut commands, &mut global, &mut server, user_key);
     }
 }
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(String::as_str) == Some(TOKEN_CMD) {
+        let client = args.get(2).expect(TOKEN_USAGE);
+        let hours = args
+            .get(3)
+            .and_then(|h| h.parse::<u64>().ok())
+            .unwrap_or(TOKEN_LIFETIME);
+
+        let lifetime = match hours.checked_mul(60 * 60) {
+            Some(secs) => Duration::from_secs(secs),
+            None => {
+                eprintln!("{}", TOKEN_USAGE);
+                return;
+            }
+        };
+
+        println!("{}", Authorization::new().issue(client, lifetime));
+        return;
+    }
+
     App::new()
+        .add_event::<GameEvent>()
         .add_plugins(MinimalPlugins)
+        .add_plugin(AccountPlugin)
+        .add_plugin(AchievementPlugin)
+        .add_plugin(AdminPlugin)
+        .add_plugin(AuthPlugin)
+        .add_plugin(ChatPlugin)
+        .add_plugin(EventLogPlugin)
+        .add_plugin(FilterPlugin)
+        .add_plugin(GuardPlugin)
         .add_plugin(HighScorePlugin)
         .add_plugin(LogPlugin)
+        .add_plugin(MetricsPlugin)
+        .add_plugin(RoomPlugin)
+        .add_plugin(RulesPlugin)
+        .add_plugin(SessionPlugin)
+        .add_plugin(ShutdownPlugin)
+        .add_plugin(StatsPlugin)
         .add_plugin(ServerPlugin::<Protocol, DefaultChannels>::new(
             ServerConfig::default(),
-            SharedConfig::default(),
+            SharedConfig {
+                tick_interval: Some(tick_duration()),
+                ..Default::default()
+            },
         ))
         .add_startup_system(setup)
         .add_system_to_stage(Stage::ReceiveEvents, authorize)
-        .add_system_to_stage(Stage::ReceiveEvents, command_message)
+        .add_system_to_stage(Stage::ReceiveEvents, boost_command.after(filter_messages))
+        .add_system_to_stage(Stage::ReceiveEvents, chat_command.after(filter_messages))
+        .add_system_to_stage(Stage::ReceiveEvents, command_message.after(filter_messages))
         .add_system_to_stage(Stage::ReceiveEvents, connect)
         .add_system_to_stage(Stage::ReceiveEvents, disconnect)
+        .add_system_to_stage(Stage::ReceiveEvents, filter_messages)
+        .add_system_to_stage(Stage::ReceiveEvents, room_command.after(filter_messages))
+        .add_system_to_stage(Stage::ReceiveEvents, stats_command.after(filter_messages))
+        .add_system_to_stage(Stage::Tick, tick_end.after(update_clock))
+        .add_system_to_stage(Stage::Tick, tick_start.before(update_scope))
         .add_system_to_stage(Stage::Tick, update_collisions.after(update_foods))
         .add_system_to_stage(Stage::Tick, update_foods.after(update_heads))
         .add_system_to_stage(Stage::Tick, update_heads.after(update_scope))
         .add_system_to_stage(Stage::Tick, update_scope)
         .add_system_to_stage(Stage::Tick, update_server.after(update_collisions))
-        .add_system_to_stage(Stage::Tick, update_time.after(update_server))
+        .add_system_to_stage(Stage::Tick, update_clock.after(update_server))
         .run();
 }
 
-fn setup(mut commands: Commands, mut server: Server<Protocol, DefaultChannels>, time: Res<Time>) {
+fn setup(mut commands: Commands, mut server: Server<Protocol, DefaultChannels>) {
     server.listen(&ServerAddrs::new(
         format!("{}:{}", SRV_ADDR, SRV_PORT).parse().unwrap(),
         format!("{}:{}", SRV_ADDR, SRV_PORT_WRTC).parse().unwrap(),
         &format!("{}://{}:{}", SRV_PROT, SRV_ADDR_PUB, SRV_PORT_WRTC),
     ));
 
+    let rules = Rules::load();
+    let main_room_key = server.make_room().key();
+    let clock = server.spawn().insert(Clock::new()).id();
+
     commands.insert_resource(Global {
-        food_timer: Timer::from_seconds(FOOD_SPAWN_DUR, true),
-        last_time: time.time_since_startup(),
-        main_room_key: server.make_room().key(),
+        clock,
+        main_room_key,
         player_heads: HashMap::new(),
         player_keys: HashMap::new(),
         player_segments: HashMap::new(),
+        rooms: HashMap::from([(main_room_key, GameRoom::main(rules.food_spawn_dur))]),
+        rules,
         segment_order: Vec::new(),
+        tick_dur: tick_duration(),
+        user_rooms: HashMap::new(),
     });
 }
 
+/// Sets the speed of a snake that ate `level` foods from the rules
+fn set_speed(global: &Global, head: &mut Head, level: usize, stepper: &mut Stepper) {
+    let boost = match (&global.rules.boost, &stepper.boost) {
+        (Some(rules), Some(_)) => rules.faktor,
+        _ => 1.,
+    };
+
+    // At most one step per tick so no collision is skipped
+    let speed = boost * global.tick_dur.as_secs_f32() / global.rules.step_dur(level);
+    stepper.speed = speed.min(1.);
+    *head.step = (global.tick_dur.as_secs_f32() / stepper.speed * 1000.) as u32;
+}
+
+/// Reads the simulation rate from `SRV_TICK_RATE`, defaulting to `TICK_RATE`.
+fn tick_duration() -> Duration {
+    let rate = env::var(TICK_RATE_VAR)
+        .ok()
+        .and_then(|r| r.parse::<u64>().ok())
+        .filter(|r| *r > 0)
+        .unwrap_or(TICK_RATE);
+
+    Duration::from_secs_f64(1. / rate as f64)
+}
+
+fn update_clock(
+    global: Res<Global>,
+    mut clocks: Query<&mut Clock>,
+    mut rooms: Local<HashSet<RoomKey>>,
+    mut server: Server<Protocol, DefaultChannels>,
+) {
+    // Every room shares the clock
+    rooms.retain(|room_key| global.rooms.contains_key(room_key));
+    for room_key in global.rooms.keys() {
+        if rooms.insert(*room_key) {
+            server.room_mut(room_key).add_entity(&global.clock);
+        }
+    }
+
+    if let Ok(mut clock) = clocks.get_mut(global.clock) {
+        *clock.tick += 1;
+    }
+}
+
 fn update_collisions(
+    mut commands: Commands,
+    mut event_log: ResMut<EventLog>,
+    mut event_writer: EventWriter<GameEvent>,
     mut global: ResMut<Global>,
-    heads: Query<&Name>,
     mut highscore: ResMut<HighScoreList>,
-    positions: Query<(Entity, &Position), With<Vincible>>,
-    scores: Query<&Score>,
+    metrics: Res<Metrics>,
+    players: Query<(&Name, &Score, &Spawned)>,
+    positions: Query<(Entity, &InRoom, &Position), With<Vincible>>,
     mut server: Server<Protocol, DefaultChannels>,
+    time: Res<Time>,
     vincibles: Query<&Vincible>,
 ) {
     let mut to_despawn = vec![];
@@ -222,97 +663,141 @@ fn update_collisions(
             continue;
         }
 
-        let (_, pos) = positions.get(*ent_head).unwrap();
-        if positions
+        let (_, room, pos) = positions.get(*ent_head).unwrap();
+        if let Some((entity, _, _)) = positions
             .iter()
-            .any(|(e, p)| e != *ent_head && *p.x == *pos.x && *p.y == *pos.y)
+            .find(|(e, r, p)| *e != *ent_head && r.0 == room.0 && *p.x == *pos.x && *p.y == *pos.y)
         {
-            to_despawn.push(*user_key);
+            let cause = match global.player_segments.get(&entity) {
+                Some(key) if *key == *user_key => DeathCause::SelfCollision,
+                _ => DeathCause::SnakeCollision,
+            };
+
+            to_despawn.push((*user_key, cause));
         }
     }
 
-    for user_key in to_despawn.iter() {
+    for (user_key, cause) in to_despawn.iter() {
         let entity = *global.player_heads.get(user_key).unwrap();
-        highscore.insert(
-            (*heads.get(entity).unwrap().text).clone(),
-            *scores.get(entity).unwrap().level,
-        );
-
-        despawn_player(&mut global, &mut server, user_key);
-        server.send_message(
+        let (name, score, _) = players.get(entity).unwrap();
+        let start = Instant::now();
+        let best = highscore.insert((*name.text).clone(), *score.level);
+        metrics.highscore_written(start.elapsed());
+        event_log.write(
             user_key,
-            DefaultChannels::UnorderedReliable,
-            &QuitCmd::new(),
+            LogEvent::HighScore {
+                best,
+                score: *score.level,
+            },
         );
+
+        if let Some(event) = death_event(&global, &players, &time, user_key, *cause) {
+            event_writer.send(event);
+        }
+
+        despawn_player(&mut commands, &mut global, &mut server, user_key);
+        let quit_cmd = QuitCmd::new();
+        metrics.message_out(&quit_cmd);
+        server.send_message(user_key, DefaultChannels::UnorderedReliable, &quit_cmd);
     }
 }
 
 fn update_foods(
+    mut commands: Commands,
     mut global: ResMut<Global>,
-    positions: Query<&Position>,
+    heads: Query<&InRoom, With<Head>>,
+    positions: Query<(&InRoom, &Position)>,
     mut server: Server<Protocol, DefaultChannels>,
-    time: Res<Time>,
 ) {
-    if global.player_heads.len() == 0 {
-        return;
-    }
+    let tick_dur = global.tick_dur;
+    for (room_key, room) in global.rooms.iter_mut() {
+        if !room.is_running() || !heads.iter().any(|r| r.0 == *room_key) {
+            continue;
+        }
+
+        if !room.food_timer.tick(tick_dur).just_finished() {
+            continue;
+        }
 
-    let delta = time.time_since_startup() - global.last_time;
-    if global.food_timer.tick(delta).just_finished() {
-        if positions.iter().count() >= GRID_SIZE.pow(2) {
-            return;
+        let taken: Vec<&Position> = positions
+            .iter()
+            .filter(|(r, _)| r.0 == *room_key)
+            .map(|(_, p)| p)
+            .collect();
+
+        if taken.len() >= GRID_SIZE.pow(2) {
+            continue;
         }
 
         let mut position = Position::rnd(GRID_SIZE);
-        while positions
+        while taken
             .iter()
             .any(|p| *p.x == *position.x && *p.y == *position.y)
         {
             position = Position::rnd(GRID_SIZE);
         }
 
-        server
+        let entity = server
             .spawn()
-            .enter_room(&global.main_room_key)
+            .enter_room(room_key)
             .insert(Food::new())
-            .insert(position);
+            .insert(position)
+            .id();
+
+        commands.entity(entity).insert(InRoom(*room_key));
     }
 }
 
 fn update_heads(
+    mut commands: Commands,
+    mut event_writer: EventWriter<GameEvent>,
     mut global: ResMut<Global>,
-    foods: Query<(Entity, &Position), (With<Food>, Without<Head>)>,
+    foods: Query<(Entity, &InRoom, &Position), (With<Food>, Without<Head>)>,
     mut heads: Query<(
         Entity,
-        &Head,
+        &mut Body,
+        &mut Head,
+        &InRoom,
+        &Name,
         &mut Score,
-        &mut TimerComponent,
+        &mut Stepper,
         &mut Position,
     )>,
-    mut segments: Query<(&mut Position, &mut Segment), (Without<Head>, Without<Food>)>,
+    mut segments: Query<&mut Position, (With<Segment>, Without<Head>, Without<Food>)>,
     mut server: Server<Protocol, DefaultChannels>,
-    time: Res<Time>,
 ) {
-    let delta = time.time_since_startup() - global.last_time;
     let global = &mut *global;
 
-    for (head_ent, head, mut score, mut timer, mut head_pos) in heads.iter_mut() {
-        if !*head.running {
+    for (head_ent, mut body, mut head, in_room, name, mut score, mut stepper, mut head_pos) in
+        heads.iter_mut()
+    {
+        let running = global
+            .rooms
+            .get(&in_room.0)
+            .map_or(false, GameRoom::is_running);
+        if !running || !*head.running {
             continue;
         }
 
         if let Some(user_key) = global.player_keys.get(&head_ent) {
-            if !timer.timer.tick(delta).just_finished() {
+            let tick_dur = global.tick_dur;
+            if let Some(timer) = stepper.boost.as_mut() {
+                if timer.tick(tick_dur).finished() {
+                    stepper.boost = None;
+                    set_speed(global, &mut head, *score.level, &mut stepper);
+                }
+            }
+
+            stepper.acc += stepper.speed;
+            if stepper.acc < 1. {
                 continue;
             }
 
+            stepper.acc -= 1.;
+
             let mut old_pos = Position::new(*head_pos.x, *head_pos.y);
-            match *head.dir {
-                Direction::Down => *head_pos.y = head_pos.y.checked_sub(1).unwrap_or(GRID_SIZE - 1),
-                Direction::Left => *head_pos.x = head_pos.x.checked_sub(1).unwrap_or(GRID_SIZE - 1),
-                Direction::Right => *head_pos.x = (*head_pos.x + 1) % GRID_SIZE,
-                Direction::Up => *head_pos.y = (*head_pos.y + 1) % GRID_SIZE,
-            }
+            head_pos.step(*head.dir);
+            body.dirs.insert(0, head.dir.opposite());
 
             for entity in global.segment_order.iter() {
                 if let Some(key) = global.player_segments.get(entity) {
@@ -320,40 +805,45 @@ fn update_heads(
                         continue;
                     }
 
-                    if let Ok((mut position, mut segment)) = segments.get_mut(*entity) {
+                    if let Ok(mut position) = segments.get_mut(*entity) {
                         let new_pos = Position::new(*position.x, *position.y);
                         *position.x = *old_pos.x;
                         *position.y = *old_pos.y;
                         *old_pos.x = *new_pos.x;
                         *old_pos.y = *new_pos.y;
-                        *segment.synced = true;
                     }
                 }
             }
 
-            if let Some((entity, _)) = foods
+            if let Some((entity, _, _)) = foods
                 .iter()
-                .find(|(_, p)| *p.x == *head_pos.x && *p.y == *head_pos.y)
+                .find(|(_, r, p)| r.0 == in_room.0 && *p.x == *head_pos.x && *p.y == *head_pos.y)
             {
                 server.entity_mut(&entity).despawn();
-                let entity = server
+                let entity = commands
                     .spawn()
-                    .enter_room(&global.main_room_key)
+                    .insert(InRoom(in_room.0))
                     .insert(old_pos)
-                    .insert(Segment::new())
+                    .insert(Segment)
                     .insert(Vincible)
                     .id();
 
                 global.player_segments.insert(entity, *user_key);
                 global.segment_order.push(entity);
 
-                let dur = timer.timer.duration().mul_f32(HEAD_MOV_DUR_FAKTOR);
-                timer.timer.set_duration(dur);
                 if *score.level < 1 {
                     server.entity_mut(&head_ent).insert(Vincible);
                 }
 
                 *score.level += 1;
+                set_speed(global, &mut head, *score.level, &mut stepper);
+                event_writer.send(GameEvent::Eat {
+                    length: *score.level + 1,
+                    name: (*name.text).clone(),
+                    user_key: *user_key,
+                });
+            } else {
+                body.dirs.pop();
             }
         }
     }
@@ -368,7 +858,3 @@ fn update_scope(mut server: Server<Protocol, DefaultChannels>) {
 fn update_server(mut server: Server<Protocol, DefaultChannels>) {
     server.send_all_updates();
 }
-
-fn update_time(mut global: ResMut<Global>, time: Res<Time>) {
-    global.last_time = time.time_since_startup();
-}
@@ -0,0 +1,269 @@
+use std::{
+    any::type_name,
+    collections::BTreeMap,
+    env,
+    fmt::Write as _,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::{Duration, Instant},
+};
+
+use agentifa_555nake_protocol::protocol::{Food, KickReason, Protocol};
+use bevy::prelude::{info, warn, Commands, Plugin, Query, Res, With};
+
+use crate::{auth::reason_label, Global};
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+const HIGHSCORE_BUCKETS: [f64; 7] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.];
+const METRICS_PATH: &str = "/metrics";
+const METRICS_VAR: &str = "SRV_METRICS_ADDR";
+const PREFIX: &str = "agentifa";
+const TICK_BUCKETS: [f64; 8] = [0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1];
+
+struct Histogram {
+    bounds: &'static [f64],
+    buckets: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Histogram {
+            bounds,
+            buckets: vec![0; bounds.len()],
+            count: 0,
+            sum: 0.,
+        }
+    }
+
+    fn observe(&mut self, duration: Duration) {
+        let secs = duration.as_secs_f64();
+        for (bound, bucket) in self.bounds.iter().zip(self.buckets.iter_mut()) {
+            if secs <= *bound {
+                *bucket += 1;
+            }
+        }
+
+        self.count += 1;
+        self.sum += secs;
+    }
+
+    fn render(&self, out: &mut String, name: &str) {
+        let _ = writeln!(out, "# TYPE {}_{} histogram", PREFIX, name);
+        for (bound, bucket) in self.bounds.iter().zip(self.buckets.iter()) {
+            let _ = writeln!(out, "{}_{}_bucket{{le=\"{}\"}} {}", PREFIX, name, bound, bucket);
+        }
+
+        let _ = writeln!(out, "{}_{}_bucket{{le=\"+Inf\"}} {}", PREFIX, name, self.count);
+        let _ = writeln!(out, "{}_{}_sum {}", PREFIX, name, self.sum);
+        let _ = writeln!(out, "{}_{}_count {}", PREFIX, name, self.count);
+    }
+}
+
+struct Values {
+    auth_rejections: BTreeMap<&'static str, u64>,
+    foods: usize,
+    highscore_writes: Histogram,
+    messages: BTreeMap<(&'static str, &'static str), u64>,
+    snakes: usize,
+    tick_durations: Histogram,
+    tick_start: Option<Instant>,
+    users: usize,
+}
+
+impl Values {
+    fn render(&self) -> String {
+        let mut out = String::new();
+        let mut gauge = |name: &str, value: usize| {
+            let _ = writeln!(out, "# TYPE {}_{} gauge", PREFIX, name);
+            let _ = writeln!(out, "{}_{} {}", PREFIX, name, value);
+        };
+
+        gauge("foods", self.foods);
+        gauge("snakes", self.snakes);
+        gauge("users", self.users);
+
+        let _ = writeln!(out, "# TYPE {}_auth_rejections_total counter", PREFIX);
+        for (reason, count) in self.auth_rejections.iter() {
+            let _ = writeln!(
+                out,
+                "{}_auth_rejections_total{{reason=\"{}\"}} {}",
+                PREFIX, reason, count
+            );
+        }
+
+        let _ = writeln!(out, "# TYPE {}_messages_total counter", PREFIX);
+        for ((direction, kind), count) in self.messages.iter() {
+            let _ = writeln!(
+                out,
+                "{}_messages_total{{direction=\"{}\",type=\"{}\"}} {}",
+                PREFIX, direction, kind, count
+            );
+        }
+
+        self.highscore_writes.render(&mut out, "highscore_write_duration_seconds");
+        self.tick_durations.render(&mut out, "tick_duration_seconds");
+        out
+    }
+}
+
+/// Server metrics in the Prometheus text format. They are served over HTTP
+/// if `SRV_METRICS_ADDR` is set, e.g. to `127.0.0.1:9555`.
+pub struct Metrics {
+    values: Arc<Mutex<Values>>,
+}
+
+impl Metrics {
+    pub fn auth_rejected(&self, reason: KickReason) {
+        *self
+            .lock()
+            .auth_rejections
+            .entry(reason_label(reason))
+            .or_insert(0) += 1;
+    }
+
+    pub fn connected(&self) {
+        self.lock().users += 1;
+    }
+
+    pub fn disconnected(&self) {
+        let mut values = self.lock();
+        values.users = values.users.saturating_sub(1);
+    }
+
+    pub fn highscore_written(&self, duration: Duration) {
+        self.lock().highscore_writes.observe(duration);
+    }
+
+    fn lock(&self) -> MutexGuard<Values> {
+        self.values.lock().unwrap()
+    }
+
+    pub fn message_in(&self, message: &Protocol) {
+        let kind = kind(message);
+        *self.lock().messages.entry(("in", kind)).or_insert(0) += 1;
+    }
+
+    /// Counts a message the server sends, labelled with the name of its type
+    pub fn message_out<M>(&self, _message: &M) {
+        let kind = type_name::<M>().rsplit("::").next().unwrap_or_default();
+        *self.lock().messages.entry(("out", kind)).or_insert(0) += 1;
+    }
+
+    fn new() -> Self {
+        Metrics {
+            values: Arc::new(Mutex::new(Values {
+                auth_rejections: BTreeMap::new(),
+                foods: 0,
+                highscore_writes: Histogram::new(&HIGHSCORE_BUCKETS),
+                messages: BTreeMap::new(),
+                snakes: 0,
+                tick_durations: Histogram::new(&TICK_BUCKETS),
+                tick_start: None,
+                users: 0,
+            })),
+        }
+    }
+}
+
+pub struct MetricsPlugin;
+
+impl Plugin for MetricsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup);
+    }
+}
+
+/// Name of the message type for the `messages_total` series
+fn kind(message: &Protocol) -> &'static str {
+    match message {
+        Protocol::AchievementMsg(_) => "AchievementMsg",
+        Protocol::AnnounceMsg(_) => "AnnounceMsg",
+        Protocol::AssignMsg(_) => "AssignMsg",
+        Protocol::Auth(_) => "Auth",
+        Protocol::Body(_) => "Body",
+        Protocol::BoostCmd(_) => "BoostCmd",
+        Protocol::ChatCmd(_) => "ChatCmd",
+        Protocol::ChatMsg(_) => "ChatMsg",
+        Protocol::Clock(_) => "Clock",
+        Protocol::DirCmd(_) => "DirCmd",
+        Protocol::Food(_) => "Food",
+        Protocol::Head(_) => "Head",
+        Protocol::HighScore(_) => "HighScore",
+        Protocol::HighScoreRank(_) => "HighScoreRank",
+        Protocol::KickMsg(_) => "KickMsg",
+        Protocol::Name(_) => "Name",
+        Protocol::PingCmd(_) => "PingCmd",
+        Protocol::PingMsg(_) => "PingMsg",
+        Protocol::Position(_) => "Position",
+        Protocol::QuitCmd(_) => "QuitCmd",
+        Protocol::RejectMsg(_) => "RejectMsg",
+        Protocol::ResumeCmd(_) => "ResumeCmd",
+        Protocol::Room(_) => "Room",
+        Protocol::RoomCmd(_) => "RoomCmd",
+        Protocol::RoomsMsg(_) => "RoomsMsg",
+        Protocol::Score(_) => "Score",
+        Protocol::StartCmd(_) => "StartCmd",
+        Protocol::StatsCmd(_) => "StatsCmd",
+        Protocol::StatsMsg(_) => "StatsMsg",
+        Protocol::Vincible(_) => "Vincible",
+    }
+}
+
+fn respond(mut stream: TcpStream, values: &Mutex<Values>) {
+    let mut request = String::new();
+    if BufReader::new(&stream).read_line(&mut request).is_err() {
+        return;
+    }
+
+    let (status, body) = match request.split_whitespace().nth(1) {
+        Some(METRICS_PATH) => ("200 OK", values.lock().unwrap().render()),
+        _ => ("404 Not Found", String::new()),
+    };
+
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nConnection: close\r\nContent-Length: {}\r\nContent-Type: {}\r\n\r\n{}",
+        status,
+        body.len(),
+        CONTENT_TYPE,
+        body
+    );
+}
+
+fn setup(mut commands: Commands) {
+    let metrics = Metrics::new();
+    if let Ok(addr) = env::var(METRICS_VAR) {
+        match TcpListener::bind(&addr) {
+            Ok(listener) => {
+                info!("serving metrics on http://{}{}", addr, METRICS_PATH);
+                let values = metrics.values.clone();
+                thread::spawn(move || {
+                    for stream in listener.incoming().flatten() {
+                        respond(stream, &values);
+                    }
+                });
+            }
+            Err(err) => warn!("can not serve metrics on {}: {}", addr, err),
+        }
+    }
+
+    commands.insert_resource(metrics);
+}
+
+pub fn tick_end(metrics: Res<Metrics>) {
+    let mut values = metrics.lock();
+    if let Some(start) = values.tick_start.take() {
+        values.tick_durations.observe(start.elapsed());
+    }
+}
+
+pub fn tick_start(foods: Query<(), With<Food>>, global: Res<Global>, metrics: Res<Metrics>) {
+    let mut values = metrics.lock();
+    values.foods = foods.iter().count();
+    values.snakes = global.player_heads.len();
+    values.tick_start = Some(Instant::now());
+}
@@ -0,0 +1,373 @@
+use std::collections::{HashMap, HashSet};
+
+use agentifa_555nake_protocol::protocol::{
+    Food, Head, Protocol, RejectReason, Room, RoomAction, RoomsMsg,
+};
+use bevy::prelude::{info, Commands, Entity, EventReader, Plugin, Query, Res, ResMut, Timer, With};
+use naia_bevy_server::{events::MessageEvent, shared::DefaultChannels, RoomKey, Server, UserKey};
+use rand::{thread_rng, Rng};
+
+use crate::{auth::Kicks, filter::NameFilter, guard::Guard, metrics::Metrics, Global, InRoom};
+
+const CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_PREFIX: char = '#';
+const CODE_SIZE: usize = 6; // Characters
+pub const ROOM_MAIN: &str = "main";
+
+/// A game of its own with separate snakes, foods and timers. The main room
+/// is public and always running. Private rooms are created by the first
+/// player naming them, start once all players are ready and can be paused
+/// by their owner. Owners may lock a room behind an invite code.
+pub struct GameRoom {
+    code: Option<String>,
+    entity: Option<Entity>, // Replicated `Room`, none for the main room
+    pub food_timer: Timer,
+    members: HashMap<UserKey, String>,
+    pub name: String,
+    owner: Option<UserKey>,
+    paused: bool,
+    ready: HashSet<UserKey>,
+    started: bool,
+}
+
+impl GameRoom {
+    pub fn is_running(&self) -> bool {
+        self.entity.is_none() || (self.started && !self.paused)
+    }
+
+    pub fn main(food_spawn_dur: f32) -> Self {
+        GameRoom {
+            code: None,
+            entity: None,
+            food_timer: Timer::from_seconds(food_spawn_dur, true),
+            members: HashMap::new(),
+            name: ROOM_MAIN.to_string(),
+            owner: None,
+            paused: false,
+            ready: HashSet::new(),
+            started: true,
+        }
+    }
+}
+
+/// Unlocked private rooms that wait for players and the users that know
+/// about them
+pub struct RoomList {
+    names: Vec<String>,
+    users: HashSet<UserKey>,
+}
+
+pub struct RoomPlugin;
+
+impl Plugin for RoomPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup).add_system(update_rooms);
+    }
+}
+
+/// Moves `user_key` into `room_key`, leaving its previous room
+pub fn enter<'world, 'state>(
+    global: &mut Global,
+    server: &mut Server<'world, 'state, Protocol, DefaultChannels>,
+    user_key: &UserKey,
+    room_key: RoomKey,
+    name: &str,
+) {
+    if global.user_rooms.get(user_key) != Some(&room_key) {
+        leave(global, server, user_key);
+        server.user_mut(user_key).enter_room(&room_key);
+        global.user_rooms.insert(*user_key, room_key);
+    }
+
+    if let Some(room) = global.rooms.get_mut(&room_key) {
+        if room.entity.is_some() {
+            room.members.insert(*user_key, name.to_string());
+        }
+    }
+}
+
+/// Moves `user_key` into the room named `room` before it starts a game. An
+/// empty name stands for the main room, unknown names create a private room
+/// owned by the user. Locked rooms are joined by `#<code>` instead of their
+/// name.
+pub fn join<'world, 'state>(
+    filter: &NameFilter,
+    global: &mut Global,
+    server: &mut Server<'world, 'state, Protocol, DefaultChannels>,
+    user_key: &UserKey,
+    name: &str,
+    room: &str,
+) -> Result<RoomKey, RejectReason> {
+    let room_key = match room.trim() {
+        "" => global.main_room_key,
+        room if room.starts_with(CODE_PREFIX) => {
+            let code = room[CODE_PREFIX.len_utf8()..].to_uppercase();
+            match global
+                .rooms
+                .iter()
+                .find(|(_, r)| r.code.as_ref() == Some(&code))
+            {
+                Some((_, r)) if r.started => return Err(RejectReason::RoomStarted),
+                Some((room_key, _)) => *room_key,
+                None => return Err(RejectReason::RoomCodeInvalid),
+            }
+        }
+        room => {
+            let room = filter
+                .validate(room)
+                .map_err(|_| RejectReason::RoomInvalid)?;
+
+            match global.rooms.iter().find(|(_, r)| r.name == room) {
+                Some((_, r)) if r.code.is_some() => return Err(RejectReason::RoomPrivate),
+                Some((_, r)) if r.entity.is_some() && r.started => {
+                    return Err(RejectReason::RoomStarted)
+                }
+                Some((room_key, _)) => *room_key,
+                None => {
+                    let room_key = server.make_room().key();
+                    let entity = server
+                        .spawn()
+                        .enter_room(&room_key)
+                        .insert(Room::new(room.clone(), name.to_string()))
+                        .id();
+
+                    info!("{} created room {}", name, room);
+                    global.rooms.insert(
+                        room_key,
+                        GameRoom {
+                            code: None,
+                            entity: Some(entity),
+                            food_timer: Timer::from_seconds(global.rules.food_spawn_dur, true),
+                            members: HashMap::new(),
+                            name: room,
+                            owner: Some(*user_key),
+                            paused: false,
+                            ready: HashSet::new(),
+                            started: false,
+                        },
+                    );
+
+                    room_key
+                }
+            }
+        }
+    };
+
+    enter(global, server, user_key, room_key, name);
+    Ok(room_key)
+}
+
+/// Removes `user_key` from its room. Owners hand a private room over to
+/// another member.
+pub fn leave<'world, 'state>(
+    global: &mut Global,
+    server: &mut Server<'world, 'state, Protocol, DefaultChannels>,
+    user_key: &UserKey,
+) {
+    let room_key = match global.user_rooms.remove(user_key) {
+        Some(room_key) => room_key,
+        None => return,
+    };
+
+    if server.user_exists(user_key) {
+        server.user_mut(user_key).leave_room(&room_key);
+    }
+
+    if let Some(room) = global.rooms.get_mut(&room_key) {
+        room.members.remove(user_key);
+        room.ready.remove(user_key);
+        if room.owner == Some(*user_key) {
+            room.owner = room.members.keys().next().copied();
+        }
+    }
+}
+
+/// Random invite code that no other room uses
+fn code(rooms: &HashMap<RoomKey, GameRoom>) -> String {
+    let mut rng = thread_rng();
+    loop {
+        let code: String = (0..CODE_SIZE)
+            .map(|_| CODE_CHARS[rng.gen_range(0..CODE_CHARS.len())] as char)
+            .collect();
+
+        if !rooms.values().any(|r| r.code.as_ref() == Some(&code)) {
+            return code;
+        }
+    }
+}
+
+/// Sorted names of the members in `keys`
+fn names<'a>(
+    members: &HashMap<UserKey, String>,
+    keys: impl Iterator<Item = &'a UserKey>,
+) -> Vec<String> {
+    let mut names: Vec<String> = keys.filter_map(|k| members.get(k).cloned()).collect();
+    names.sort();
+    names
+}
+
+pub fn room_command(
+    mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
+    mut global: ResMut<Global>,
+    guard: Res<Guard>,
+    kicks: Res<Kicks>,
+) {
+    let global = &mut *global;
+    for event in event_reader.iter() {
+        let (user_key, cmd) = match event {
+            MessageEvent(user_key, _, Protocol::RoomCmd(cmd)) => (user_key, cmd),
+            _ => continue,
+        };
+
+        if guard.ignores(&kicks, user_key) {
+            continue;
+        }
+
+        let new_code = match *cmd.action {
+            RoomAction::Lock => Some(code(&global.rooms)),
+            _ => None,
+        };
+
+        let playing = global.player_heads.contains_key(user_key);
+        let room = match global.user_rooms.get(user_key) {
+            Some(room_key) => match global.rooms.get_mut(room_key) {
+                Some(room) if room.entity.is_some() => room,
+                _ => continue,
+            },
+            None => continue,
+        };
+
+        let owner = room.owner == Some(*user_key);
+        match *cmd.action {
+            RoomAction::Lock if owner => {
+                room.code = match room.code {
+                    Some(_) => None,
+                    None => new_code,
+                };
+            }
+            RoomAction::Pause if owner && room.started => room.paused = true,
+            RoomAction::Ready if playing && !room.started => {
+                if !room.ready.remove(user_key) {
+                    room.ready.insert(*user_key);
+                }
+            }
+            RoomAction::Resume if owner => room.paused = false,
+            _ => (),
+        }
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.insert_resource(RoomList {
+        names: Vec::new(),
+        users: HashSet::new(),
+    });
+}
+
+/// Starts private rooms once all players are ready, resets them after the
+/// round and closes them when they are empty. Replicates their state and
+/// sends the list of rooms that wait for players.
+fn update_rooms(
+    foods: Query<(Entity, &InRoom), With<Food>>,
+    mut global: ResMut<Global>,
+    heads: Query<&InRoom, With<Head>>,
+    mut list: ResMut<RoomList>,
+    metrics: Res<Metrics>,
+    mut rooms: Query<&mut Room>,
+    mut server: Server<Protocol, DefaultChannels>,
+) {
+    let global = &mut *global;
+    let mut closed = vec![];
+    for (room_key, room) in global.rooms.iter_mut() {
+        let entity = match room.entity {
+            Some(entity) => entity,
+            None => continue,
+        };
+
+        let alive = heads.iter().any(|r| r.0 == *room_key);
+        if room.members.is_empty() && !alive {
+            closed.push(*room_key);
+            continue;
+        }
+
+        let players: Vec<UserKey> = room
+            .members
+            .keys()
+            .filter(|k| global.player_heads.contains_key(k))
+            .copied()
+            .collect();
+
+        if !room.started && !players.is_empty() && players.iter().all(|k| room.ready.contains(k)) {
+            info!("room {} started", room.name);
+            room.started = true;
+        } else if room.started && !alive {
+            info!("room {} finished", room.name);
+            room.paused = false;
+            room.ready.clear();
+            room.started = false;
+        }
+
+        if let Ok(mut state) = rooms.get_mut(entity) {
+            let owner = room.owner.iter().find_map(|k| room.members.get(k));
+            let owner = owner.cloned().unwrap_or_default();
+            let players = names(&room.members, players.iter());
+            let ready = names(&room.members, room.ready.iter());
+            let code = room.code.clone().unwrap_or_default();
+            if *state.code != code {
+                *state.code = code;
+            }
+            if *state.owner != owner {
+                *state.owner = owner;
+            }
+            if *state.paused != room.paused {
+                *state.paused = room.paused;
+            }
+            if *state.players != players {
+                *state.players = players;
+            }
+            if *state.ready != ready {
+                *state.ready = ready;
+            }
+            if *state.started != room.started {
+                *state.started = room.started;
+            }
+        }
+    }
+
+    for room_key in closed {
+        if let Some(room) = global.rooms.remove(&room_key) {
+            for (entity, in_room) in foods.iter() {
+                if in_room.0 == room_key {
+                    server.entity_mut(&entity).despawn();
+                }
+            }
+
+            if let Some(entity) = room.entity {
+                server.entity_mut(&entity).despawn();
+            }
+
+            server.room_mut(&room_key).destroy();
+            info!("room {} closed", room.name);
+        }
+    }
+
+    let mut names: Vec<String> = global
+        .rooms
+        .values()
+        .filter(|r| r.code.is_none() && r.entity.is_some() && !r.started)
+        .map(|r| r.name.clone())
+        .collect();
+
+    names.sort();
+    let changed = names != list.names;
+    list.names = names;
+    list.users.retain(|k| server.user_exists(k));
+    for user_key in server.user_keys() {
+        let new = list.users.insert(user_key);
+        if changed || new {
+            let msg = RoomsMsg::new(list.names.clone());
+            metrics.message_out(&msg);
+            server.send_message(&user_key, DefaultChannels::UnorderedReliable, &msg);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    time::{Duration, SystemTime},
+};
+
+use agentifa_555nake_protocol::protocol::{AnnounceMsg, Head, Protocol, Score};
+use bevy::prelude::{info, warn, Commands, Plugin, Query, Res, ResMut, Time, Timer};
+use naia_bevy_server::{shared::DefaultChannels, Server};
+use serde::{Deserialize, Serialize};
+
+use crate::{metrics::Metrics, set_speed, Global, Stepper};
+
+const BOOST_DUR_MAX: f32 = 60.0; // Seconds
+const BOOST_FAKTOR_MAX: f32 = 10.0;
+const FOOD_SPAWN_DUR: f32 = 10.0; // Seconds
+pub const FOOD_SPAWN_DUR_MAX: f32 = 3600.0; // Seconds
+const HEAD_MOV_DUR_FAKTOR: f32 = 0.95;
+const HEAD_MOV_DUR_MIN: f32 = 0.1; // Seconds
+const HEAD_MOV_DUR_MAX: f32 = 60.0; // Seconds
+const HEAD_MOV_DUR_START: f32 = 0.5; // Seconds
+const NOTICE_RULES: &str = "The game rules were updated";
+const RULES: &str = "rules.json";
+const RULES_POLL_DUR: f32 = 1.0; // Seconds
+
+/// Boost triggered by a `BoostCmd`. It multiplies the speed of the snake by
+/// `faktor` for `dur` seconds and costs `cost` segments.
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+pub struct BoostRules {
+    pub cost: usize,
+    pub dur: f32, // Seconds
+    pub faktor: f32,
+}
+
+/// Game rules read from `rules.json`. Missing fields keep their defaults.
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Rules {
+    pub boost: Option<BoostRules>,
+    pub food_spawn_dur: f32,
+    pub speed: SpeedCurve,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            boost: None,
+            food_spawn_dur: FOOD_SPAWN_DUR,
+            speed: SpeedCurve::Exponential {
+                faktor: HEAD_MOV_DUR_FAKTOR,
+                min: HEAD_MOV_DUR_MIN,
+                start: HEAD_MOV_DUR_START,
+            },
+        }
+    }
+}
+
+impl Rules {
+    /// Reads the rules file, falling back to the defaults if it is missing
+    /// or invalid.
+    pub fn load() -> Self {
+        read().unwrap_or_default()
+    }
+
+    /// Writes the rules to the rules file
+    pub fn save(&self) {
+        if let Ok(file) = File::create(RULES) {
+            let writer = BufWriter::new(file);
+            let _ = serde_json::to_writer_pretty(writer, self);
+        }
+    }
+
+    /// Seconds per step of a snake that ate `level` foods
+    pub fn step_dur(&self, level: usize) -> f32 {
+        match &self.speed {
+            SpeedCurve::Exponential { faktor, min, start } => {
+                (start * faktor.powi(level as i32)).max(*min)
+            }
+            SpeedCurve::Linear { delta, min, start } => (start - delta * level as f32).max(*min),
+            SpeedCurve::Stepped { start, steps } => steps
+                .range(..=level)
+                .next_back()
+                .map_or(*start, |(_, d)| *d),
+        }
+    }
+
+    /// Checks that every value is finite and within its bounds, so that no
+    /// duration built from the rules can overflow.
+    fn is_valid(&self) -> bool {
+        let boost = self.boost.as_ref().map_or(true, |b| {
+            within(b.dur, BOOST_DUR_MAX) && (1. ..=BOOST_FAKTOR_MAX).contains(&b.faktor)
+        });
+
+        let step = |d: &f32| within(*d, HEAD_MOV_DUR_MAX);
+        let speed = match &self.speed {
+            SpeedCurve::Exponential { faktor, min, start } => {
+                within(*faktor, 1.) && step(min) && step(start)
+            }
+            SpeedCurve::Linear { delta, min, start } => {
+                (0. ..=HEAD_MOV_DUR_MAX).contains(delta) && step(min) && step(start)
+            }
+            SpeedCurve::Stepped { start, steps } => step(start) && steps.values().all(step),
+        };
+
+        boost && speed && within(self.food_spawn_dur, FOOD_SPAWN_DUR_MAX)
+    }
+}
+
+/// Seconds per step depending on the number of foods eaten
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "curve")]
+pub enum SpeedCurve {
+    /// `start` times `faktor` per food, at least `min`
+    Exponential { faktor: f32, min: f32, start: f32 },
+    /// `start` minus `delta` per food, at least `min`
+    Linear { delta: f32, min: f32, start: f32 },
+    /// `start` until the snake ate as many foods as a key of `steps`
+    Stepped {
+        start: f32,
+        steps: BTreeMap<usize, f32>,
+    },
+}
+
+/// Polls the modification time of the rules file
+struct RulesWatcher {
+    modified: Option<SystemTime>,
+    timer: Timer,
+}
+
+pub struct RulesPlugin;
+
+impl Plugin for RulesPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup).add_system(update_rules);
+    }
+}
+
+fn modified() -> Option<SystemTime> {
+    fs::metadata(RULES).and_then(|m| m.modified()).ok()
+}
+
+fn read() -> Option<Rules> {
+    let file = File::open(RULES).ok()?;
+    match serde_json::from_reader::<_, Rules>(BufReader::new(file)) {
+        Ok(rules) if rules.is_valid() => Some(rules),
+        Ok(_) => {
+            warn!("{} contains invalid rules", RULES);
+            None
+        }
+        Err(err) => {
+            warn!("can not read {}: {}", RULES, err);
+            None
+        }
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.insert_resource(RulesWatcher {
+        modified: modified(),
+        timer: Timer::from_seconds(RULES_POLL_DUR, true),
+    });
+}
+
+/// Applies changes of the rules file to the running game. Systems outside of
+/// the naia stages run between ticks, so no tick sees half of the rules.
+fn update_rules(
+    mut global: ResMut<Global>,
+    mut heads: Query<(&mut Head, &Score, &mut Stepper)>,
+    metrics: Res<Metrics>,
+    mut server: Server<Protocol, DefaultChannels>,
+    time: Res<Time>,
+    mut watcher: ResMut<RulesWatcher>,
+) {
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let modified = modified();
+    if modified.is_none() || modified == watcher.modified {
+        return;
+    }
+
+    watcher.modified = modified;
+    let rules = match read() {
+        Some(rules) if rules != global.rules => rules,
+        _ => return,
+    };
+
+    info!("rules changed: {}", serde_json::to_string(&rules).unwrap());
+
+    for room in global.rooms.values_mut() {
+        let dur = Duration::from_secs_f32(rules.food_spawn_dur);
+        room.food_timer.set_duration(dur);
+    }
+
+    global.rules = rules;
+    for (mut head, score, mut stepper) in heads.iter_mut() {
+        set_speed(&global, &mut head, *score.level, &mut stepper);
+    }
+
+    let msg = AnnounceMsg::new(NOTICE_RULES.to_string());
+    for user_key in server.user_keys() {
+        metrics.message_out(&msg);
+        server.send_message(&user_key, DefaultChannels::UnorderedReliable, &msg);
+    }
+}
+
+/// Returns true if `value` is above zero and at most `max`. NaN and infinite
+/// values are out of range.
+fn within(value: f32, max: f32) -> bool {
+    value > 0. && value <= max
+}
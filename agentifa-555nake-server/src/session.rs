@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use agentifa_555nake_protocol::protocol::{Name, Protocol, Score};
+use bevy::prelude::{Commands, Entity, EventWriter, Plugin, Query, Res, ResMut, Time, Timer};
+use naia_bevy_server::{shared::DefaultChannels, Server, UserKey};
+use rand::random;
+
+use crate::{death_event, despawn_player, DeathCause, GameEvent, Global, Spawned};
+
+const SESSION_DUR: f32 = 30.0; // Seconds
+
+/// A frozen snake whose user lost the connection
+struct Orphan {
+    entity: Entity,
+    timer: Timer,
+    user_key: UserKey,
+}
+
+/// Session tokens of running games. A client that reconnects within
+/// `SESSION_DUR` can reclaim its snake with the token of its `AssignMsg`.
+pub struct Sessions {
+    orphans: HashMap<String, Orphan>,
+    tokens: HashMap<UserKey, String>,
+}
+
+impl Sessions {
+    pub fn issue(&mut self, user_key: &UserKey) -> String {
+        let token = format!("{:032x}", random::<u128>());
+        self.tokens.insert(*user_key, token.clone());
+        token
+    }
+
+    /// Keeps the snake `entity` of the disconnected `user_key` alive. Returns
+    /// false if the user has no session.
+    pub fn orphan(&mut self, user_key: &UserKey, entity: Entity) -> bool {
+        match self.tokens.remove(user_key) {
+            Some(token) => {
+                self.orphans.insert(
+                    token,
+                    Orphan {
+                        entity,
+                        timer: Timer::from_seconds(SESSION_DUR, false),
+                        user_key: *user_key,
+                    },
+                );
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Heads of the orphaned snakes and the user keys they belonged to
+    pub fn orphans(&self) -> impl Iterator<Item = (&UserKey, &Entity)> {
+        self.orphans.values().map(|o| (&o.user_key, &o.entity))
+    }
+
+    pub fn remove(&mut self, user_key: &UserKey) {
+        self.tokens.remove(user_key);
+    }
+
+    /// Hands the orphaned snake of `token` over to `user_key`. Returns the
+    /// head and the user key it belonged to before.
+    pub fn resume(&mut self, token: &str, user_key: &UserKey) -> Option<(Entity, UserKey)> {
+        let orphan = self.orphans.remove(token)?;
+        self.tokens.insert(*user_key, token.to_string());
+        Some((orphan.entity, orphan.user_key))
+    }
+}
+
+pub struct SessionPlugin;
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup).add_system(update_sessions);
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.insert_resource(Sessions {
+        orphans: HashMap::new(),
+        tokens: HashMap::new(),
+    });
+}
+
+fn update_sessions(
+    mut commands: Commands,
+    mut event_writer: EventWriter<GameEvent>,
+    mut global: ResMut<Global>,
+    players: Query<(&Name, &Score, &Spawned)>,
+    mut server: Server<Protocol, DefaultChannels>,
+    mut sessions: ResMut<Sessions>,
+    time: Res<Time>,
+) {
+    let mut expired = vec![];
+    sessions.orphans.retain(|_, orphan| {
+        let retain = !orphan.timer.tick(time.delta()).finished();
+        if !retain {
+            expired.push((orphan.entity, orphan.user_key));
+        }
+
+        retain
+    });
+
+    for (entity, user_key) in expired {
+        // Register the head under its old key so the regular death handling applies
+        global.player_heads.insert(user_key, entity);
+        if let Some(event) = death_event(&global, &players, &time, &user_key, DeathCause::Quit) {
+            event_writer.send(event);
+        }
+
+        despawn_player(&mut commands, &mut global, &mut server, &user_key);
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use agentifa_555nake_protocol::protocol::{KickReason, Name, Protocol, Score};
+use bevy::{
+    app::AppExit,
+    prelude::{info, warn, Commands, EventWriter, Plugin, Query, Res, ResMut, Time, Timer},
+};
+use naia_bevy_server::{shared::DefaultChannels, Server};
+
+use crate::{
+    auth::Kicks,
+    eventlog::{EventLog, LogEvent},
+    highscore::HighScoreList,
+    metrics::Metrics,
+    session::Sessions,
+    Global,
+};
+
+const SHUTDOWN_DUR: f32 = 2.0; // Seconds to deliver the notice before exiting
+
+/// Stops the server on SIGINT, SIGTERM or the `shutdown` admin command.
+/// Running games are recorded in the highscore and every user is kicked with
+/// `KickReason::Shutdown` before the app exits.
+pub struct Shutdown {
+    requested: Arc<AtomicBool>,
+    timer: Option<Timer>,
+}
+
+impl Shutdown {
+    /// Returns true once the server stopped accepting players
+    pub fn is_active(&self) -> bool {
+        self.timer.is_some()
+    }
+
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+    }
+}
+
+pub struct ShutdownPlugin;
+
+impl Plugin for ShutdownPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup).add_system(update_shutdown);
+    }
+}
+
+fn setup(mut commands: Commands) {
+    let requested = Arc::new(AtomicBool::new(false));
+    let handler = requested.clone();
+    if let Err(err) = ctrlc::set_handler(move || handler.store(true, Ordering::SeqCst)) {
+        warn!("can not handle termination signals: {}", err);
+    }
+
+    commands.insert_resource(Shutdown {
+        requested,
+        timer: None,
+    });
+}
+
+fn update_shutdown(
+    mut app_exit: EventWriter<AppExit>,
+    mut event_log: ResMut<EventLog>,
+    global: Res<Global>,
+    mut highscore: ResMut<HighScoreList>,
+    mut kicks: ResMut<Kicks>,
+    metrics: Res<Metrics>,
+    players: Query<(&Name, &Score)>,
+    mut server: Server<Protocol, DefaultChannels>,
+    sessions: Res<Sessions>,
+    mut shutdown: ResMut<Shutdown>,
+    time: Res<Time>,
+) {
+    if let Some(timer) = shutdown.timer.as_mut() {
+        if timer.tick(time.delta()).just_finished() {
+            info!("shutdown complete");
+            app_exit.send(AppExit);
+        }
+
+        return;
+    }
+
+    if !shutdown.requested.load(Ordering::SeqCst) {
+        return;
+    }
+
+    info!("shutting down");
+    // Orphaned snakes of disconnected users count as running games as well
+    for (user_key, entity) in global.player_heads.iter().chain(sessions.orphans()) {
+        if let Ok((name, score)) = players.get(*entity) {
+            let best = highscore.insert((*name.text).clone(), *score.level);
+            let score = *score.level;
+            event_log.write(user_key, LogEvent::HighScore { best, score });
+        }
+    }
+
+    for user_key in server.user_keys() {
+        if !kicks.contains(&user_key) {
+            kicks.kick(&metrics, &mut server, &user_key, KickReason::Shutdown);
+        }
+    }
+
+    shutdown.timer = Some(Timer::from_seconds(SHUTDOWN_DUR, false));
+}
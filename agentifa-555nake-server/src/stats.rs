@@ -0,0 +1,151 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+};
+
+use agentifa_555nake_protocol::protocol::{Protocol, StatsMsg};
+use bevy::prelude::{Commands, EventReader, Plugin, Res, ResMut, Time, Timer};
+use naia_bevy_server::{events::MessageEvent, shared::DefaultChannels, Server};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::Kicks, guard::Guard, metrics::Metrics, shutdown::Shutdown, DeathCause, GameEvent,
+};
+
+const STATS: &str = "stats.json";
+const STATS_SAVE_DUR: f32 = 30.0; // Seconds
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct Stats {
+    pub deaths: HashMap<DeathCause, usize>,
+    pub food: usize,
+    pub games: usize,
+    pub score: usize,
+    pub survival: u64,
+}
+
+impl Stats {
+    pub fn deaths(&self, cause: DeathCause) -> usize {
+        *self.deaths.get(&cause).unwrap_or(&0)
+    }
+}
+
+/// Changes are kept in memory and written to `STATS` every `STATS_SAVE_DUR`
+/// and once the server shuts down.
+pub struct StatsList {
+    dirty: bool,
+    entries: HashMap<String, Stats>,
+    timer: Timer,
+}
+
+impl StatsList {
+    pub fn get(&self, name: &str) -> Option<&Stats> {
+        self.entries.get(name)
+    }
+
+    fn new() -> Self {
+        let mut entries = HashMap::new();
+        if let Ok(file) = File::open(STATS) {
+            let reader = BufReader::new(file);
+            if let Ok(list) = serde_json::from_reader(reader) {
+                entries = list;
+            }
+        }
+
+        StatsList {
+            dirty: false,
+            entries,
+            timer: Timer::from_seconds(STATS_SAVE_DUR, true),
+        }
+    }
+
+    fn save(&self) {
+        if let Ok(file) = File::create(STATS) {
+            let writer = BufWriter::new(file);
+            let _ = serde_json::to_writer(writer, &self.entries);
+        }
+    }
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(setup)
+            .add_system(save_stats)
+            .add_system(update_stats);
+    }
+}
+
+fn save_stats(mut list: ResMut<StatsList>, shutdown: Res<Shutdown>, time: Res<Time>) {
+    let finished = list.timer.tick(time.delta()).just_finished();
+    if list.dirty && (finished || shutdown.is_active()) {
+        list.save();
+        list.dirty = false;
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.insert_resource(StatsList::new());
+}
+
+pub fn stats_command(
+    mut event_reader: EventReader<MessageEvent<Protocol, DefaultChannels>>,
+    guard: Res<Guard>,
+    kicks: Res<Kicks>,
+    list: Res<StatsList>,
+    metrics: Res<Metrics>,
+    mut server: Server<Protocol, DefaultChannels>,
+) {
+    for event in event_reader.iter() {
+        if let MessageEvent(user_key, _, Protocol::StatsCmd(msg)) = event {
+            if guard.ignores(&kicks, user_key) {
+                continue;
+            }
+
+            let stats_msg = match list.get(&msg.name) {
+                Some(entry) => StatsMsg::new(
+                    (*msg.name).clone(),
+                    entry.games,
+                    entry.food,
+                    entry.score,
+                    entry.survival,
+                    entry.deaths(DeathCause::SelfCollision),
+                    entry.deaths(DeathCause::SnakeCollision),
+                    entry.deaths(DeathCause::Quit),
+                ),
+                None => StatsMsg::new((*msg.name).clone(), 0, 0, 0, 0, 0, 0, 0),
+            };
+
+            metrics.message_out(&stats_msg);
+            server.send_message(user_key, DefaultChannels::UnorderedReliable, &stats_msg);
+        }
+    }
+}
+
+pub fn update_stats(mut event_reader: EventReader<GameEvent>, mut list: ResMut<StatsList>) {
+    for event in event_reader.iter() {
+        match event {
+            GameEvent::Death {
+                cause,
+                name,
+                score,
+                survival,
+                ..
+            } => {
+                let stats = list.entries.entry(name.clone()).or_default();
+                stats.games += 1;
+                stats.score += *score;
+                stats.survival = stats.survival.max(survival.as_secs());
+                *stats.deaths.entry(*cause).or_insert(0) += 1;
+                list.dirty = true;
+            }
+            GameEvent::Eat { name, .. } => {
+                list.entries.entry(name.clone()).or_default().food += 1;
+                list.dirty = true;
+            }
+            _ => (),
+        }
+    }
+}